@@ -0,0 +1,232 @@
+use super::{TaskBackend, WorkflowLists};
+use crate::schemas::*;
+use crate::utils::{get_github_config, get_github_list_labels};
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, LINK};
+use reqwest::RequestBuilder;
+use serde_json::json;
+
+const PAGE_SIZE: usize = 100;
+
+/// Issues on a GitHub-compatible tracker (GitHub, Gitea, or a mock) as cards.
+///
+/// Workflow lists map onto labels: an open issue sits in the in-progress
+/// list if it carries that label and in todo otherwise, and closed issues
+/// are done. Moving a card swaps the workflow label and opens/closes the
+/// issue to match. Checklist items become Markdown task-list entries in the
+/// issue body.
+pub struct GitHubBackend {
+    client: reqwest::Client,
+    api_url: String,
+    repo: String,
+    token: String,
+    lists: WorkflowLists,
+}
+
+impl GitHubBackend {
+    pub fn from_env() -> Self {
+        let (api_url, repo, token) = get_github_config();
+        let (todo, in_progress, done) = get_github_list_labels();
+
+        Self::new(api_url, repo, token, WorkflowLists { todo, in_progress, done })
+    }
+
+    pub fn new(api_url: String, repo: String, token: String, lists: WorkflowLists) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url,
+            repo,
+            token,
+            lists,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/repos/{}{}", self.api_url, self.repo, path))
+            .header("Authorization", format!("token {}", self.token))
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "warp-tasks-mcp")
+    }
+
+    async fn get_issue(&self, card_id: &str) -> Result<GitHubIssue> {
+        let issue = self.request(reqwest::Method::GET, &format!("/issues/{}", card_id))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(issue)
+    }
+
+    /// Every issue in `state` (`open`, `closed` or `all`) as a card.
+    async fn list_issues(&self, state: &str) -> Result<Vec<TaskCard>> {
+        let mut cards = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let response = self.request(reqwest::Method::GET, "/issues")
+                .query(&[
+                    ("state", state.to_string()),
+                    ("per_page", PAGE_SIZE.to_string()),
+                    ("page", page.to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?;
+            let more = has_next_page(response.headers());
+            let issues: Vec<GitHubIssue> = response.json().await?;
+
+            let fetched = issues.len();
+            // The issues endpoint also returns pull requests; those are not tasks
+            cards.extend(
+                issues.into_iter()
+                    .filter(|i| i.pull_request.is_none())
+                    .map(|i| self.to_card(i)),
+            );
+
+            if fetched == 0 || !more {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(cards)
+    }
+
+    fn is_workflow_label(&self, name: &str) -> bool {
+        name == self.lists.todo || name == self.lists.in_progress || name == self.lists.done
+    }
+
+    pub(crate) fn to_card(&self, issue: GitHubIssue) -> TaskCard {
+        let closed = issue.state == "closed";
+        let list_id = if closed {
+            self.lists.done.clone()
+        } else if issue.labels.iter().any(|l| l.name == self.lists.in_progress) {
+            self.lists.in_progress.clone()
+        } else {
+            self.lists.todo.clone()
+        };
+
+        TaskCard {
+            id: issue.number.to_string(),
+            name: issue.title,
+            description: issue.body.unwrap_or_default(),
+            list_id,
+            closed,
+            url: issue.html_url.clone(),
+            short_url: issue.html_url,
+            due: None,
+            due_complete: closed,
+            labels: issue.labels.into_iter().map(|l| TaskLabel {
+                id: l.id.to_string(),
+                name: l.name,
+                color: l.color,
+            }).collect(),
+            members: issue.assignees.into_iter().map(|u| u.login).collect(),
+        }
+    }
+}
+
+/// Whether the page these headers came with has a successor. Servers may
+/// return fewer than `per_page` items on any page (Gitea caps it at
+/// `MAX_RESPONSE_ITEMS`, 50 by default), so a short page proves nothing;
+/// the `Link` header does. Without one, we keep going until a page is empty.
+fn has_next_page(headers: &HeaderMap) -> bool {
+    match headers.get(LINK) {
+        Some(link) => link.to_str().is_ok_and(|link| link.contains("rel=\"next\"")),
+        None => true,
+    }
+}
+
+#[async_trait]
+impl TaskBackend for GitHubBackend {
+    fn name(&self) -> &str {
+        "github"
+    }
+
+    fn workflow_lists(&self) -> WorkflowLists {
+        self.lists.clone()
+    }
+
+    async fn list_cards(&self) -> Result<Vec<TaskCard>> {
+        self.list_issues("all").await
+    }
+
+    // Closed issues only grow as the project ages
+    async fn list_open_cards(&self) -> Result<Vec<TaskCard>> {
+        self.list_issues("open").await
+    }
+
+    async fn get_card(&self, card_id: &str) -> Result<TaskCard> {
+        Ok(self.to_card(self.get_issue(card_id).await?))
+    }
+
+    async fn list_lists(&self) -> Result<Vec<TaskList>> {
+        Ok([&self.lists.todo, &self.lists.in_progress, &self.lists.done]
+            .into_iter()
+            .map(|id| TaskList {
+                id: id.clone(),
+                name: id.clone(),
+                closed: false,
+            })
+            .collect())
+    }
+
+    async fn add_comment(&self, card_id: &str, text: &str) -> Result<()> {
+        self.request(reqwest::Method::POST, &format!("/issues/{}/comments", card_id))
+            .json(&json!({ "body": text }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn move_card(&self, card_id: &str, list_id: &str) -> Result<()> {
+        if !self.is_workflow_label(list_id) {
+            return Err(anyhow::anyhow!(
+                "Unknown list '{}'; expected one of '{}', '{}', '{}'",
+                list_id, self.lists.todo, self.lists.in_progress, self.lists.done
+            ));
+        }
+
+        // Swap the workflow label, keeping every other label on the issue
+        let issue = self.get_issue(card_id).await?;
+        let mut labels: Vec<String> = issue.labels.into_iter()
+            .map(|l| l.name)
+            .filter(|name| !self.is_workflow_label(name))
+            .collect();
+        labels.push(list_id.to_string());
+
+        self.request(reqwest::Method::PUT, &format!("/issues/{}/labels", card_id))
+            .json(&json!({ "labels": labels }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let state = if list_id == self.lists.done { "closed" } else { "open" };
+        self.request(reqwest::Method::PATCH, &format!("/issues/{}", card_id))
+            .json(&json!({ "state": state }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn update_description(&self, card_id: &str, description: &str) -> Result<()> {
+        self.request(reqwest::Method::PATCH, &format!("/issues/{}", card_id))
+            .json(&json!({ "body": description }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn add_checklist_item(&self, card_id: &str, item: &str) -> Result<()> {
+        let body = self.get_issue(card_id).await?.body.unwrap_or_default();
+        let separator = if body.is_empty() || body.ends_with('\n') { "" } else { "\n" };
+        let body = format!("{}{}- [ ] {}\n", body, separator, item);
+        self.update_description(card_id, &body).await
+    }
+}
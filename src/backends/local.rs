@@ -0,0 +1,304 @@
+use super::{TaskBackend, WorkflowLists};
+use crate::schemas::{TaskCard, TaskLabel, TaskList};
+use anyhow::Result;
+use async_trait::async_trait;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+const FRONT_MATTER: &str = "---";
+/// Advisory lock file in the task directory, shared by every process (one
+/// per stdio agent) working on the same board
+const LOCK_FILE: &str = ".tasks.lock";
+
+/// A task board kept as a directory of files in the repository itself.
+///
+/// Each task is either a Markdown file with YAML front matter (the body is
+/// the description) or a plain `.yaml`/`.yml` file. The file stem is the
+/// card id and the `status` field is the list the card sits in:
+///
+/// ```markdown
+/// ---
+/// title: Add retry logic to the sync job
+/// status: todo
+/// labels: [backend]
+/// checklist:
+///   - text: reproduce failure
+///     done: true
+/// comments:
+///   - text: "Task claimed by agent: agent-7"
+///     at: 1718000000
+/// ---
+/// The nightly sync gives up after the first 502 ...
+/// ```
+pub struct LocalBackend {
+    dir: PathBuf,
+    lists: WorkflowLists,
+    // Serialises read-modify-write cycles within this process, so only one of
+    // them at a time waits on the file lock
+    write_lock: Mutex<()>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct TaskFile {
+    title: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    assignees: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    closed: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    checklist: Vec<ChecklistItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    comments: Vec<TaskComment>,
+    // Only used by plain YAML files; Markdown tasks keep it in the body
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ChecklistItem {
+    text: String,
+    #[serde(default)]
+    done: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct TaskComment {
+    text: String,
+    at: i64,
+}
+
+impl LocalBackend {
+    pub fn new(dir: impl Into<PathBuf>, lists: WorkflowLists) -> Self {
+        Self {
+            dir: dir.into(),
+            lists,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn is_task_file(path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("md") | Some("yaml") | Some("yml")
+        )
+    }
+
+    fn is_markdown(path: &Path) -> bool {
+        path.extension().and_then(|e| e.to_str()) == Some("md")
+    }
+
+    async fn task_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.dir).await.map_err(|e| {
+            anyhow::anyhow!("Cannot read task directory {}: {}", self.dir.display(), e)
+        })?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.is_file() && Self::is_task_file(&path) {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+
+    async fn find_path(&self, card_id: &str) -> Result<PathBuf> {
+        self.task_paths()
+            .await?
+            .into_iter()
+            .find(|p| p.file_stem().and_then(|s| s.to_str()) == Some(card_id))
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found in {}", card_id, self.dir.display()))
+    }
+
+    async fn read_task(&self, path: &Path) -> Result<TaskFile> {
+        let raw = tokio::fs::read_to_string(path).await?;
+        let mut task = if Self::is_markdown(path) {
+            parse_markdown(&raw)
+        } else {
+            serde_yaml::from_str(&raw).map_err(anyhow::Error::from)
+        }
+        .map_err(|e| anyhow::anyhow!("Invalid task file {}: {}", path.display(), e))?;
+
+        if task.status.is_empty() {
+            task.status = self.lists.todo.clone();
+        }
+        Ok(task)
+    }
+
+    async fn write_task(&self, path: &Path, task: &TaskFile) -> Result<()> {
+        let contents = if Self::is_markdown(path) {
+            render_markdown(task)?
+        } else {
+            serde_yaml::to_string(task)?
+        };
+
+        // Write to a sibling file and rename so readers never see a torn
+        // file. The name is unique so concurrent writers never share one
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        let tmp = path.with_file_name(format!(".{}.{}.tmp", stem, Uuid::new_v4()));
+        tokio::fs::write(&tmp, contents).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(())
+    }
+
+    /// Takes the board's advisory lock, blocking until other processes let
+    /// go of it. Released when the returned file is dropped.
+    async fn lock_board(&self) -> Result<std::fs::File> {
+        let path = self.dir.join(LOCK_FILE);
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?;
+            file.lock()?;
+            Ok(file)
+        })
+        .await?
+    }
+
+    async fn modify(&self, card_id: &str, f: impl FnOnce(&mut TaskFile) + Send) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let _board = self.lock_board().await?;
+        let path = self.find_path(card_id).await?;
+        let mut task = self.read_task(&path).await?;
+        f(&mut task);
+        self.write_task(&path, &task).await
+    }
+
+    fn to_card(&self, path: &Path, task: TaskFile) -> TaskCard {
+        let id = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        let url = format!("file://{}", path.display());
+        let checklist_done = !task.checklist.is_empty() && task.checklist.iter().all(|i| i.done);
+
+        TaskCard {
+            id,
+            name: task.title,
+            description: task.description,
+            closed: task.closed,
+            url: url.clone(),
+            short_url: url,
+            due: task.due,
+            due_complete: task.closed || checklist_done,
+            labels: task.labels.into_iter().map(|name| TaskLabel {
+                id: name.clone(),
+                name,
+                color: None,
+            }).collect(),
+            members: task.assignees,
+            list_id: task.status,
+        }
+    }
+}
+
+fn parse_markdown(raw: &str) -> Result<TaskFile> {
+    let rest = raw
+        .strip_prefix(FRONT_MATTER)
+        .ok_or_else(|| anyhow::anyhow!("missing YAML front matter"))?;
+    let end = rest
+        .find(&format!("\n{}", FRONT_MATTER))
+        .ok_or_else(|| anyhow::anyhow!("unterminated YAML front matter"))?;
+
+    let mut task: TaskFile = serde_yaml::from_str(&rest[..end])?;
+    let body = &rest[end + FRONT_MATTER.len() + 1..];
+    task.description = body.trim().to_string();
+    Ok(task)
+}
+
+fn render_markdown(task: &TaskFile) -> Result<String> {
+    let mut front = task.clone();
+    let description = std::mem::take(&mut front.description);
+    let front_matter = serde_yaml::to_string(&front)?;
+
+    Ok(format!("{FRONT_MATTER}\n{front_matter}{FRONT_MATTER}\n\n{description}\n"))
+}
+
+#[async_trait]
+impl TaskBackend for LocalBackend {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn workflow_lists(&self) -> WorkflowLists {
+        self.lists.clone()
+    }
+
+    async fn list_cards(&self) -> Result<Vec<TaskCard>> {
+        let mut cards = Vec::new();
+        for path in self.task_paths().await? {
+            // One broken file should not hide the rest of the board
+            match self.read_task(&path).await {
+                Ok(task) => cards.push(self.to_card(&path, task)),
+                Err(e) => warn!("Skipping unreadable task file {}: {}", path.display(), e),
+            }
+        }
+        Ok(cards)
+    }
+
+    async fn get_card(&self, card_id: &str) -> Result<TaskCard> {
+        let path = self.find_path(card_id).await?;
+        let task = self.read_task(&path).await?;
+        Ok(self.to_card(&path, task))
+    }
+
+    async fn list_lists(&self) -> Result<Vec<TaskList>> {
+        // The workflow lists always exist; any other status used by a task
+        // file shows up as an extra list.
+        let mut ids = vec![
+            self.lists.todo.clone(),
+            self.lists.in_progress.clone(),
+            self.lists.done.clone(),
+        ];
+        for card in self.list_cards().await? {
+            if !ids.contains(&card.list_id) {
+                ids.push(card.list_id);
+            }
+        }
+
+        Ok(ids.into_iter().map(|id| TaskList {
+            name: id.clone(),
+            id,
+            closed: false,
+        }).collect())
+    }
+
+    async fn add_comment(&self, card_id: &str, text: &str) -> Result<()> {
+        let comment = TaskComment {
+            text: text.to_string(),
+            at: chrono::Utc::now().timestamp(),
+        };
+        self.modify(card_id, |task| task.comments.push(comment)).await
+    }
+
+    async fn move_card(&self, card_id: &str, list_id: &str) -> Result<()> {
+        if list_id.is_empty() {
+            return Err(anyhow::anyhow!("Cannot move task {} to an empty list id", card_id));
+        }
+        let list_id = list_id.to_string();
+        self.modify(card_id, |task| task.status = list_id).await
+    }
+
+    async fn update_description(&self, card_id: &str, description: &str) -> Result<()> {
+        let description = description.to_string();
+        self.modify(card_id, |task| task.description = description).await
+    }
+
+    async fn add_checklist_item(&self, card_id: &str, item: &str) -> Result<()> {
+        let item = ChecklistItem {
+            text: item.to_string(),
+            done: false,
+        };
+        self.modify(card_id, |task| task.checklist.push(item)).await
+    }
+}
@@ -0,0 +1,126 @@
+use super::{TaskBackend, WorkflowLists};
+use crate::schemas::{TaskCard, TaskList};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// In-process fake board used to run the task tools without network access.
+pub struct MemoryBackend {
+    lists: Vec<TaskList>,
+    cards: Mutex<Vec<TaskCard>>,
+    pub comments: Mutex<HashMap<String, Vec<String>>>,
+    pub checklists: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl MemoryBackend {
+    /// A board with `todo`, `in_progress` and `done` lists and no cards.
+    pub fn new() -> Self {
+        let lists = ["todo", "in_progress", "done"]
+            .iter()
+            .map(|id| TaskList {
+                id: id.to_string(),
+                name: id.to_string(),
+                closed: false,
+            })
+            .collect();
+
+        Self {
+            lists,
+            cards: Mutex::new(Vec::new()),
+            comments: Mutex::new(HashMap::new()),
+            checklists: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn add_card(&self, id: &str, name: &str, list_id: &str) {
+        self.cards.lock().unwrap().push(TaskCard {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            list_id: list_id.to_string(),
+            closed: false,
+            url: format!("memory://card/{}", id),
+            short_url: format!("memory://card/{}", id),
+            due: None,
+            due_complete: false,
+            labels: Vec::new(),
+            members: Vec::new(),
+        });
+    }
+
+    fn with_card<T>(&self, card_id: &str, f: impl FnOnce(&mut TaskCard) -> T) -> Result<T> {
+        let mut cards = self.cards.lock().unwrap();
+        let card = cards
+            .iter_mut()
+            .find(|c| c.id == card_id)
+            .ok_or_else(|| anyhow::anyhow!("Card {} not found", card_id))?;
+        Ok(f(card))
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TaskBackend for MemoryBackend {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn workflow_lists(&self) -> WorkflowLists {
+        WorkflowLists {
+            todo: "todo".to_string(),
+            in_progress: "in_progress".to_string(),
+            done: "done".to_string(),
+        }
+    }
+
+    async fn list_cards(&self) -> Result<Vec<TaskCard>> {
+        Ok(self.cards.lock().unwrap().clone())
+    }
+
+    async fn get_card(&self, card_id: &str) -> Result<TaskCard> {
+        self.with_card(card_id, |card| card.clone())
+    }
+
+    async fn list_lists(&self) -> Result<Vec<TaskList>> {
+        Ok(self.lists.clone())
+    }
+
+    async fn add_comment(&self, card_id: &str, text: &str) -> Result<()> {
+        self.with_card(card_id, |_| ())?;
+        self.comments
+            .lock()
+            .unwrap()
+            .entry(card_id.to_string())
+            .or_default()
+            .push(text.to_string());
+        Ok(())
+    }
+
+    async fn move_card(&self, card_id: &str, list_id: &str) -> Result<()> {
+        if !self.lists.iter().any(|l| l.id == list_id) {
+            return Err(anyhow::anyhow!("List {} not found", list_id));
+        }
+        self.with_card(card_id, |card| card.list_id = list_id.to_string())
+    }
+
+    async fn update_description(&self, card_id: &str, description: &str) -> Result<()> {
+        self.with_card(card_id, |card| card.description = description.to_string())
+    }
+
+    async fn add_checklist_item(&self, card_id: &str, item: &str) -> Result<()> {
+        self.with_card(card_id, |_| ())?;
+        self.checklists
+            .lock()
+            .unwrap()
+            .entry(card_id.to_string())
+            .or_default()
+            .push(item.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::schemas::{TaskCard, TaskList};
+use crate::utils::{get_local_list_ids, get_task_backend, get_tasks_dir};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub mod github;
+pub mod local;
+#[cfg(test)]
+pub mod memory;
+pub mod trello;
+
+#[cfg(test)]
+mod tests;
+
+/// The list ids that make up the todo -> in progress -> done workflow on a
+/// backend's board.
+#[derive(Debug, Clone)]
+pub struct WorkflowLists {
+    pub todo: String,
+    pub in_progress: String,
+    pub done: String,
+}
+
+/// A task tracker the task tools can coordinate agents on.
+///
+/// Assignment/lease bookkeeping lives in Redis and is shared by every
+/// backend; implementations only deal with the tracker's own cards, lists
+/// and comments.
+#[async_trait]
+pub trait TaskBackend: Send + Sync {
+    /// Short identifier used in logs, e.g. `"trello"`.
+    fn name(&self) -> &str;
+
+    fn workflow_lists(&self) -> WorkflowLists;
+
+    async fn list_cards(&self) -> Result<Vec<TaskCard>>;
+
+    /// The cards that are not closed, for scans that have no use for done
+    /// ones. By default this is every card; trackers where closed cards
+    /// pile up without bound (issue trackers) leave them out.
+    async fn list_open_cards(&self) -> Result<Vec<TaskCard>> {
+        self.list_cards().await
+    }
+
+    async fn get_card(&self, card_id: &str) -> Result<TaskCard>;
+
+    async fn list_lists(&self) -> Result<Vec<TaskList>>;
+
+    /// Tracker-side effects of an agent claiming a card. By default this
+    /// just leaves a comment on the card.
+    async fn on_claimed(&self, card_id: &str, agent_id: &str) -> Result<()> {
+        self.add_comment(card_id, &format!("Task claimed by agent: {}", agent_id))
+            .await
+    }
+
+    async fn add_comment(&self, card_id: &str, text: &str) -> Result<()>;
+
+    async fn move_card(&self, card_id: &str, list_id: &str) -> Result<()>;
+
+    async fn update_description(&self, card_id: &str, description: &str) -> Result<()>;
+
+    /// Appends `item` to the card's progress checklist, creating the
+    /// checklist if needed.
+    async fn add_checklist_item(&self, card_id: &str, item: &str) -> Result<()>;
+}
+
+/// Builds the backend selected by `TASK_BACKEND`.
+pub fn from_env() -> Result<Arc<dyn TaskBackend>> {
+    match get_task_backend().as_str() {
+        "trello" => Ok(Arc::new(trello::TrelloBackend::from_env())),
+        "local" => {
+            let (todo, in_progress, done) = get_local_list_ids();
+            let lists = WorkflowLists { todo, in_progress, done };
+            Ok(Arc::new(local::LocalBackend::new(get_tasks_dir(), lists)))
+        }
+        "github" => Ok(Arc::new(github::GitHubBackend::from_env())),
+        other => Err(anyhow::anyhow!("Unknown TASK_BACKEND: {}", other)),
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::github::GitHubBackend;
+    use crate::backends::{TaskBackend, WorkflowLists};
+    use crate::schemas::GitHubIssue;
+    use axum::extract::Query;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    fn backend() -> GitHubBackend {
+        backend_at("http://localhost:3000/api/v1")
+    }
+
+    fn backend_at(api_url: &str) -> GitHubBackend {
+        let lists = WorkflowLists {
+            todo: "todo".to_string(),
+            in_progress: "in progress".to_string(),
+            done: "done".to_string(),
+        };
+        GitHubBackend::new(api_url.to_string(), "team/app".to_string(), "t".to_string(), lists)
+    }
+
+    fn issue(number: u64, state: &str, labels: &[&str]) -> GitHubIssue {
+        serde_json::from_value(json!({
+            "number": number,
+            "title": format!("Issue {}", number),
+            "body": null,
+            "state": state,
+            "html_url": format!("http://localhost:3000/team/app/issues/{}", number),
+            "labels": labels.iter().enumerate()
+                .map(|(i, name)| json!({"id": i, "name": name, "color": "ededed"}))
+                .collect::<Vec<_>>(),
+            "assignees": [{"login": "octocat"}]
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_issue_labels_and_state_map_to_workflow_lists() {
+        let backend = backend();
+
+        let open = backend.to_card(issue(1, "open", &["bug"]));
+        assert_eq!(open.id, "1");
+        assert_eq!(open.list_id, "todo");
+        assert_eq!(open.members, vec!["octocat"]);
+
+        let working = backend.to_card(issue(2, "open", &["bug", "in progress"]));
+        assert_eq!(working.list_id, "in progress");
+
+        let closed = backend.to_card(issue(3, "closed", &["in progress"]));
+        assert_eq!(closed.list_id, "done");
+        assert!(closed.closed);
+    }
+
+    /// Serves 120 issues 50 to a page whatever `per_page` asks for, as Gitea
+    /// does by default, with `Link` headers if `links` is set.
+    async fn serve_issues(links: bool) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}/api/v1", listener.local_addr().unwrap());
+        let issues = move |Query(query): Query<HashMap<String, String>>| async move {
+            let page: usize = query["page"].parse().unwrap();
+            let issues: Vec<Value> = (1..=120)
+                .skip((page - 1) * 50)
+                .take(50)
+                .map(|number| json!({
+                    "number": number, "title": "Issue", "body": null, "state": "open",
+                    "html_url": "", "labels": [], "assignees": []
+                }))
+                .collect();
+            if !links {
+                return Json(issues).into_response();
+            }
+            let mut link = format!("<http://mock/issues?page={}>; rel=\"prev\"", page.saturating_sub(1));
+            if page < 3 {
+                link = format!("<http://mock/issues?page={}>; rel=\"next\", {}", page + 1, link);
+            }
+            ([("link", link)], Json(issues)).into_response()
+        };
+        let router = Router::new().route("/api/v1/repos/team/app/issues", get(issues));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        api_url
+    }
+
+    #[tokio::test]
+    async fn test_list_cards_reads_past_short_pages() {
+        for links in [true, false] {
+            let cards = backend_at(&serve_issues(links).await).list_cards().await.unwrap();
+            assert_eq!(cards.len(), 120, "with Link headers: {}", links);
+        }
+    }
+
+    /// Serves two open issues and one closed one, honouring `state`.
+    async fn serve_open_and_closed() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}/api/v1", listener.local_addr().unwrap());
+        let issues = |Query(query): Query<HashMap<String, String>>| async move {
+            let issues: Vec<Value> = [(1, "open"), (2, "open"), (3, "closed")]
+                .into_iter()
+                .filter(|(_, state)| query["state"] == "all" || &query["state"] == state)
+                .filter(|_| query["page"] == "1")
+                .map(|(number, state)| json!({
+                    "number": number, "title": "Issue", "body": null, "state": state,
+                    "html_url": "", "labels": [], "assignees": []
+                }))
+                .collect();
+            Json(issues)
+        };
+        let router = Router::new().route("/api/v1/repos/team/app/issues", get(issues));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        api_url
+    }
+
+    #[tokio::test]
+    async fn test_open_cards_skip_closed_issues() {
+        let backend = backend_at(&serve_open_and_closed().await);
+
+        let open = backend.list_open_cards().await.unwrap();
+        assert_eq!(open.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), vec!["1", "2"]);
+        let all = backend.list_cards().await.unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all.iter().any(|c| c.id == "3" && c.list_id == "done"));
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::local::LocalBackend;
+    use crate::backends::{TaskBackend, WorkflowLists};
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    fn board() -> (LocalBackend, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("warp-tasks-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(dir.join("fix-sync.md"), "---\ntitle: Fix the sync job\nlabels: [backend]\n---\n\nThe nightly sync gives up after one 502.\n").unwrap();
+        std::fs::write(dir.join("docs.yaml"), "title: Write docs\nstatus: in_progress\ndescription: Cover the local backend\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a task").unwrap();
+
+        let lists = WorkflowLists {
+            todo: "todo".to_string(),
+            in_progress: "in_progress".to_string(),
+            done: "done".to_string(),
+        };
+        (LocalBackend::new(&dir, lists), dir)
+    }
+
+    #[tokio::test]
+    async fn test_reads_markdown_and_yaml_tasks() {
+        let (backend, dir) = board();
+
+        let cards = backend.list_cards().await.unwrap();
+        assert_eq!(cards.len(), 2);
+
+        let fix = backend.get_card("fix-sync").await.unwrap();
+        assert_eq!(fix.name, "Fix the sync job");
+        assert_eq!(fix.list_id, "todo"); // no status defaults to the todo list
+        assert_eq!(fix.description, "The nightly sync gives up after one 502.");
+        assert_eq!(fix.labels[0].name, "backend");
+
+        let docs = backend.get_card("docs").await.unwrap();
+        assert_eq!(docs.list_id, "in_progress");
+        assert_eq!(docs.description, "Cover the local backend");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_written_back_to_the_file() {
+        let (backend, dir) = board();
+
+        backend.add_comment("fix-sync", "Task claimed by agent: agent-1").await.unwrap();
+        backend.add_checklist_item("fix-sync", "reproduce the 502").await.unwrap();
+        backend.move_card("fix-sync", "done").await.unwrap();
+        backend.update_description("docs", "Cover both backends").await.unwrap();
+
+        let raw = std::fs::read_to_string(dir.join("fix-sync.md")).unwrap();
+        assert!(raw.starts_with("---\n"));
+        assert!(raw.contains("status: done"));
+        assert!(raw.contains("reproduce the 502"));
+        assert!(raw.trim_end().ends_with("The nightly sync gives up after one 502."));
+
+        let fix = backend.get_card("fix-sync").await.unwrap();
+        assert_eq!(fix.list_id, "done");
+        assert_eq!(backend.get_card("docs").await.unwrap().description, "Cover both backends");
+        assert!(backend.move_card("missing", "done").await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unparsable_task_files_are_skipped() {
+        let (backend, dir) = board();
+        std::fs::write(dir.join("broken.md"), "---
+title: [unclosed
+---
+").unwrap();
+
+        let cards = backend.list_cards().await.unwrap();
+        assert_eq!(cards.len(), 2);
+        assert!(backend.get_card("broken").await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writers_keep_each_others_changes() {
+        let (backend, dir) = board();
+        let lists = backend.workflow_lists();
+        // Separate backends stand in for agents running in separate processes
+        let backends: Vec<_> = (0..4)
+            .map(|_| std::sync::Arc::new(LocalBackend::new(&dir, lists.clone())))
+            .collect();
+
+        let writes: Vec<_> = backends
+            .iter()
+            .enumerate()
+            .flat_map(|(agent, backend)| (0..5).map(move |n| (agent, n, backend.clone())))
+            .map(|(agent, n, backend)| {
+                let text = format!("agent-{} note {}", agent, n);
+                tokio::spawn(async move { backend.add_comment("fix-sync", &text).await })
+            })
+            .collect();
+        for write in writes {
+            write.await.unwrap().unwrap();
+        }
+
+        let raw = std::fs::read_to_string(dir.join("fix-sync.md")).unwrap();
+        assert_eq!(raw.matches(" note ").count(), 20);
+        // No temporary files are left behind
+        let leftovers = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter(|e| e.as_ref().unwrap().path().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
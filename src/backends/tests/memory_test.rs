@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::memory::MemoryBackend;
+    use crate::backends::{TaskBackend, WorkflowLists};
+    use crate::schemas::{TaskCard, TaskList};
+    use crate::server::MCPServer;
+    use crate::tests::support::{call_tool, server_with_board, test_redis};
+    use crate::tools::assignment;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use redis::AsyncCommands;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    /// A `MemoryBackend` whose tracker fails every claim announcement.
+    struct ClaimRefusingBackend(MemoryBackend);
+
+    #[async_trait]
+    impl TaskBackend for ClaimRefusingBackend {
+        fn name(&self) -> &str {
+            "claim-refusing"
+        }
+
+        fn workflow_lists(&self) -> WorkflowLists {
+            self.0.workflow_lists()
+        }
+
+        async fn list_cards(&self) -> Result<Vec<TaskCard>> {
+            self.0.list_cards().await
+        }
+
+        async fn get_card(&self, card_id: &str) -> Result<TaskCard> {
+            self.0.get_card(card_id).await
+        }
+
+        async fn list_lists(&self) -> Result<Vec<TaskList>> {
+            self.0.list_lists().await
+        }
+
+        async fn on_claimed(&self, card_id: &str, _agent_id: &str) -> Result<()> {
+            Err(anyhow::anyhow!("Tracker rejected the claim on {}", card_id))
+        }
+
+        async fn add_comment(&self, card_id: &str, text: &str) -> Result<()> {
+            self.0.add_comment(card_id, text).await
+        }
+
+        async fn move_card(&self, card_id: &str, list_id: &str) -> Result<()> {
+            self.0.move_card(card_id, list_id).await
+        }
+
+        async fn update_description(&self, card_id: &str, description: &str) -> Result<()> {
+            self.0.update_description(card_id, description).await
+        }
+
+        async fn add_checklist_item(&self, card_id: &str, item: &str) -> Result<()> {
+            self.0.add_checklist_item(card_id, item).await
+        }
+    }
+
+    fn text(result: &Value) -> Value {
+        serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_task_lifecycle_against_memory_backend() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, backend) = server_with_board(test_redis().await, &card_id).await;
+
+        // Card shows up as available
+        let scan = call_tool(&server, "scan_trello_tasks", json!({"list_filter": "todo"})).await;
+        let cards = text(&scan)["cards"].as_array().unwrap().clone();
+        let card = cards.iter().find(|c| c["id"] == card_id.as_str()).unwrap();
+        assert_eq!(card["status"], "available");
+
+        // First claim wins, second one is rejected
+        let claim = call_tool(&server, "take_trello_task", json!({"agent_id": "agent-a", "card_id": card_id})).await;
+        let lease_token = claim["structuredContent"]["lease_token"].as_str().unwrap().to_string();
+        assert_eq!(text(&claim), claim["structuredContent"]);
+        let stolen = call_tool(&server, "take_trello_task", json!({"agent_id": "agent-b", "card_id": card_id})).await;
+        assert_eq!(stolen["isError"], true);
+
+        // Updates need the lease token
+        let forged = call_tool(&server, "update_trello_task", json!({
+            "agent_id": "agent-a", "card_id": card_id, "update_type": "comment",
+            "content": "progress", "lease_token": "not-the-token"
+        })).await;
+        assert_eq!(forged["isError"], true);
+
+        let update = call_tool(&server, "update_trello_task", json!({
+            "agent_id": "agent-a", "card_id": card_id, "update_type": "checklist",
+            "content": "write intro", "lease_token": lease_token
+        })).await;
+        assert!(update.get("isError").is_none());
+        assert_eq!(backend.checklists.lock().unwrap()[&card_id], vec!["write intro"]);
+
+        // Completing moves the card to done and frees it up
+        let done = call_tool(&server, "complete_trello_task", json!({
+            "agent_id": "agent-a", "card_id": card_id, "lease_token": lease_token,
+            "summary": "docs written"
+        })).await;
+        assert!(done.get("isError").is_none());
+        assert_eq!(backend.get_card(&card_id).await.unwrap().list_id, "done");
+
+        let comments = backend.comments.lock().unwrap()[&card_id].clone();
+        assert_eq!(comments.first().unwrap(), "Task claimed by agent: agent-a");
+        assert!(comments.last().unwrap().starts_with("Task completed by agent agent-a"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_claiming_a_missing_card_leaves_no_lease_behind() {
+        let (server, backend) = server_with_board(test_redis().await, &Uuid::new_v4().to_string()).await;
+        let card_id = Uuid::new_v4().to_string();
+
+        let missing = call_tool(&server, "take_trello_task", json!({"agent_id": "agent-a", "card_id": card_id})).await;
+        assert_eq!(missing["isError"], true);
+
+        // Once the card exists anyone can claim it
+        backend.add_card(&card_id, "Late card", "todo");
+        let claim = call_tool(&server, "take_trello_task", json!({"agent_id": "agent-b", "card_id": card_id})).await;
+        assert!(claim.get("isError").is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_failed_claim_announcement_gives_the_lease_back() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let card_id = Uuid::new_v4().to_string();
+        let agent_id = format!("agent-{}", Uuid::new_v4());
+        let board = MemoryBackend::new();
+        board.add_card(&card_id, "Write the docs", "todo");
+        let server = Arc::new(MCPServer::new(redis, Arc::new(ClaimRefusingBackend(board))).await.unwrap());
+
+        let claim = call_tool(&server, "take_trello_task", json!({"agent_id": agent_id, "card_id": card_id})).await;
+        assert_eq!(claim["isError"], true);
+
+        let lease: Option<String> = conn.get(assignment::assignment_key(&card_id)).await.unwrap();
+        assert!(lease.is_none());
+        let indexed: Option<String> = conn.hget(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+        assert!(indexed.is_none());
+        let tasks: Vec<String> = conn.smembers(format!("agent:{}:tasks", agent_id)).await.unwrap();
+        assert!(!tasks.contains(&card_id));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_racing_agents_get_one_lease_between_them() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, _) = server_with_board(test_redis().await, &card_id).await;
+
+        let take = |agent_id: &str| call_tool(&server, "take_trello_task", json!({"agent_id": agent_id, "card_id": card_id}));
+        let (a, b) = tokio::join!(take("agent-a"), take("agent-b"));
+
+        let winners: Vec<&Value> = [&a, &b].into_iter().filter(|claim| claim.get("isError").is_none()).collect();
+        assert_eq!(winners.len(), 1, "{} / {}", a, b);
+        assert!(winners[0]["structuredContent"]["lease_token"].is_string());
+        let loser = if winners[0] == &a { &b } else { &a };
+        assert_eq!(loser["isError"], true);
+    }
+}
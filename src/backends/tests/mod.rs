@@ -0,0 +1,3 @@
+mod github_test;
+mod local_test;
+mod memory_test;
@@ -0,0 +1,199 @@
+use super::{TaskBackend, WorkflowLists};
+use crate::schemas::*;
+use crate::utils::{get_trello_config, get_trello_list_ids};
+use anyhow::Result;
+use async_trait::async_trait;
+use log::{debug, error};
+use serde_json::{json, Value};
+
+const TRELLO_API: &str = "https://api.trello.com/1";
+const PROGRESS_CHECKLIST: &str = "Progress";
+
+pub struct TrelloBackend {
+    client: reqwest::Client,
+    key: String,
+    token: String,
+    board_id: String,
+    lists: WorkflowLists,
+}
+
+impl TrelloBackend {
+    pub fn from_env() -> Self {
+        let (key, token, board_id) = get_trello_config();
+        let (todo, in_progress, done) = get_trello_list_ids();
+
+        Self {
+            client: reqwest::Client::new(),
+            key,
+            token,
+            board_id,
+            lists: WorkflowLists { todo, in_progress, done },
+        }
+    }
+
+    fn auth(&self) -> [(&str, &str); 2] {
+        [("key", self.key.as_str()), ("token", self.token.as_str())]
+    }
+}
+
+impl From<TrelloCard> for TaskCard {
+    fn from(card: TrelloCard) -> Self {
+        Self {
+            id: card.id,
+            name: card.name,
+            description: card.desc,
+            list_id: card.id_list,
+            closed: card.closed,
+            url: card.url,
+            short_url: card.short_url,
+            due: card.due,
+            due_complete: card.due_complete,
+            labels: card.labels.into_iter().map(|l| TaskLabel {
+                id: l.id,
+                name: l.name,
+                color: l.color,
+            }).collect(),
+            members: card.id_members,
+        }
+    }
+}
+
+impl From<TrelloList> for TaskList {
+    fn from(list: TrelloList) -> Self {
+        Self {
+            id: list.id,
+            name: list.name,
+            closed: list.closed,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskBackend for TrelloBackend {
+    fn name(&self) -> &str {
+        "trello"
+    }
+
+    fn workflow_lists(&self) -> WorkflowLists {
+        self.lists.clone()
+    }
+
+    async fn list_cards(&self) -> Result<Vec<TaskCard>> {
+        let url = format!("{}/boards/{}/cards", TRELLO_API, self.board_id);
+
+        debug!("Fetching Trello cards from {}", url);
+
+        let response = self.client.get(&url)
+            .header("Accept", "application/json")
+            .query(&self.auth())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get cards: {}", response.status()));
+        }
+
+        let response_text = response.text().await?;
+        let cards: Vec<TrelloCard> = match serde_json::from_str(&response_text) {
+            Ok(cards) => cards,
+            Err(e) => {
+                error!("Failed to parse Trello card list: {}", e);
+                debug!("Response text: {}", response_text);
+                return Err(anyhow::anyhow!("Failed to parse card list: {}", e));
+            }
+        };
+
+        Ok(cards.into_iter().map(TaskCard::from).collect())
+    }
+
+    async fn get_card(&self, card_id: &str) -> Result<TaskCard> {
+        let card: TrelloCard = self.client.get(format!("{}/cards/{}", TRELLO_API, card_id))
+            .query(&self.auth())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(card.into())
+    }
+
+    async fn list_lists(&self) -> Result<Vec<TaskList>> {
+        let lists: Vec<TrelloList> = self.client.get(format!("{}/boards/{}/lists", TRELLO_API, self.board_id))
+            .query(&self.auth())
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(lists.into_iter().map(TaskList::from).collect())
+    }
+
+    async fn add_comment(&self, card_id: &str, text: &str) -> Result<()> {
+        self.client.post(format!("{}/cards/{}/actions/comments", TRELLO_API, card_id))
+            .query(&self.auth())
+            .json(&json!({ "text": text }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn move_card(&self, card_id: &str, list_id: &str) -> Result<()> {
+        self.client.put(format!("{}/cards/{}", TRELLO_API, card_id))
+            .query(&self.auth())
+            .json(&json!({ "idList": list_id }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn update_description(&self, card_id: &str, description: &str) -> Result<()> {
+        self.client.put(format!("{}/cards/{}", TRELLO_API, card_id))
+            .query(&self.auth())
+            .json(&json!({ "desc": description }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn add_checklist_item(&self, card_id: &str, item: &str) -> Result<()> {
+        let checklists: Vec<Value> = self.client.get(format!("{}/cards/{}/checklists", TRELLO_API, card_id))
+            .query(&self.auth())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let existing = checklists.iter()
+            .find(|c| c.get("name").and_then(|n| n.as_str()) == Some(PROGRESS_CHECKLIST))
+            .and_then(|c| c.get("id").and_then(|id| id.as_str()).map(str::to_string));
+
+        let checklist_id = match existing {
+            Some(id) => id,
+            None => {
+                let created: Value = self.client.post(format!("{}/cards/{}/checklists", TRELLO_API, card_id))
+                    .query(&self.auth())
+                    .json(&json!({ "name": PROGRESS_CHECKLIST, "pos": "bottom" }))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                created.get("id")
+                    .and_then(|id| id.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("Trello did not return a checklist id"))?
+            }
+        };
+
+        self.client.post(format!("{}/checklists/{}/checkItems", TRELLO_API, checklist_id))
+            .query(&self.auth())
+            .json(&json!({ "name": item }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use crate::backends::TaskBackend;
+use crate::schemas::{KnowledgeEntry, KNOWLEDGE_CATEGORIES};
+use crate::tools::memory;
+use crate::utils::RedisManager;
+use anyhow::Result;
+use redis::AsyncCommands;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/// MCP caps a completion response at this many values.
+const MAX_VALUES: usize = 100;
+
+/// Completion request for something we do not know; maps to JSON-RPC code
+/// -32602.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidCompletionRequest(pub String);
+
+/// Suggests values for `argument` starting with `value`, drawn from live
+/// board and Redis data. Returns the MCP `completion` object.
+///
+/// Arguments are recognized by name, so the same suggestions serve tool
+/// arguments, prompt arguments and resource template variables.
+pub async fn complete(
+    redis: &RedisManager,
+    backend: &dyn TaskBackend,
+    argument: &str,
+    value: &str,
+) -> Result<Value> {
+    let needle = value.to_lowercase();
+    let mut matches = BTreeSet::new();
+
+    match argument {
+        "card_id" => {
+            // Agents often remember the title rather than the id
+            for card in backend.list_cards().await? {
+                if starts_with(&card.id, &needle) || card.name.to_lowercase().contains(&needle) {
+                    matches.insert(card.id);
+                }
+            }
+        }
+        "list_id" => {
+            for list in backend.list_lists().await? {
+                if starts_with(&list.id, &needle) || list.name.to_lowercase().contains(&needle) {
+                    matches.insert(list.id);
+                }
+            }
+        }
+        "category" | "category_filter" => {
+            let stored = knowledge_values(redis, |entry| vec![entry.category]).await?;
+            let known = KNOWLEDGE_CATEGORIES.iter().map(|c| c.to_string());
+            matches.extend(known.chain(stored).filter(|c| starts_with(c, &needle)));
+        }
+        "tags" | "tag" => {
+            let stored = knowledge_values(redis, |entry| entry.tags).await?;
+            matches.extend(stored.into_iter().filter(|t| starts_with(t, &needle)));
+        }
+        // The `knowledge://{id}` resource template
+        "id" => {
+            let stored = knowledge_values(redis, |entry| vec![entry.id]).await?;
+            matches.extend(stored.into_iter().filter(|id| starts_with(id, &needle)));
+        }
+        "agent_id" | "agent_filter" => {
+            let mut conn = redis.get_connection().await?;
+            let agents: Vec<String> = conn.zrange("active_agents", 0, -1).await?;
+            matches.extend(agents.into_iter().filter(|a| starts_with(a, &needle)));
+        }
+        // Nothing to suggest, which is not an error
+        _ => {}
+    }
+
+    let total = matches.len();
+    let values: Vec<String> = matches.into_iter().take(MAX_VALUES).collect();
+    Ok(json!({
+        "values": values,
+        "total": total,
+        "hasMore": total > MAX_VALUES
+    }))
+}
+
+fn starts_with(candidate: &str, needle: &str) -> bool {
+    candidate.to_lowercase().starts_with(needle)
+}
+
+/// Collects values out of every stored knowledge entry.
+async fn knowledge_values(
+    redis: &RedisManager,
+    extract: impl Fn(KnowledgeEntry) -> Vec<String>,
+) -> Result<Vec<String>> {
+    let entries = memory::all_knowledge(redis).await?;
+    Ok(entries.into_iter().flat_map(extract).collect())
+}
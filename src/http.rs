@@ -0,0 +1,257 @@
+use crate::server::MCPServer;
+use crate::utils::{get_allowed_origins, get_auth_token, get_max_concurrent_requests, get_session_idle_timeout};
+use anyhow::Result;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use log::info;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::{OwnedMutexGuard, Semaphore};
+use tokio_stream::Stream;
+use uuid::Uuid;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+struct Session {
+    server: Arc<MCPServer>,
+    last_seen: Instant,
+}
+
+struct HttpState {
+    server: Arc<MCPServer>,
+    sessions: Mutex<HashMap<String, Session>>,
+    // Shared by every session: they all work the same Redis and tracker
+    slots: Arc<Semaphore>,
+}
+
+impl HttpState {
+    fn session(&self, headers: &HeaderMap) -> Result<(String, Arc<MCPServer>), (StatusCode, &'static str)> {
+        let id = headers
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .ok_or((StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"))?;
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(id).ok_or((StatusCode::NOT_FOUND, "Unknown session"))?;
+        session.last_seen = Instant::now();
+        Ok((id.to_string(), session.server.clone()))
+    }
+
+    /// Closes sessions that sent nothing for `idle`. A session with an open
+    /// event stream is in use however quiet it is.
+    fn evict_idle(&self, idle: Duration) {
+        self.sessions.lock().unwrap().retain(|id, session| {
+            let streaming = session.server.outbound().try_lock().is_err();
+            let keep = streaming || session.last_seen.elapsed() < idle;
+            if !keep {
+                info!("Closed idle MCP session {}", id);
+            }
+            keep
+        });
+    }
+}
+
+/// Serves MCP over Streamable HTTP on a single `/mcp` endpoint, so several
+/// agents can share one server instead of each spawning its own.
+///
+/// * `POST` carries one JSON-RPC message or batch. `initialize` opens a
+///   session and returns its id in the `Mcp-Session-Id` header, which every
+///   later request must send back. Requests are answered in the response
+///   body; notifications get `202 Accepted`.
+/// * `GET` opens a Server-Sent Events stream of the session's
+///   server-initiated messages (progress, resource updates).
+/// * `DELETE` ends the session. Sessions left idle for
+///   `MCP_SESSION_IDLE_TIMEOUT` seconds are ended too.
+///
+/// Every request must carry `Authorization: Bearer <MCP_AUTH_TOKEN>` when a
+/// token is set. Binding to anything but loopback without one is refused.
+pub async fn serve(server: Arc<MCPServer>) -> Result<()> {
+    let token = get_auth_token();
+    if token.is_none() && !is_loopback(server.address()) {
+        anyhow::bail!(
+            "Refusing to serve on {} without MCP_AUTH_TOKEN; set one or bind to a loopback address",
+            server.address()
+        );
+    }
+    server.spawn_reaper();
+
+    let address = format!("{}:{}", server.address(), server.port());
+    let listener = tokio::net::TcpListener::bind(&address).await?;
+    info!("Warp MCP server listening on http://{}/mcp", address);
+    axum::serve(listener, router(server, token, get_max_concurrent_requests())).await?;
+    Ok(())
+}
+
+/// The `/mcp` endpoint. `server` only mints sessions; it never answers
+/// requests itself. With a `token`, requests without it are turned away.
+/// At most `max_concurrent` requests run at once across all sessions.
+pub(crate) fn router(server: Arc<MCPServer>, token: Option<String>, max_concurrent: usize) -> Router {
+    let state = Arc::new(HttpState {
+        server,
+        sessions: Mutex::new(HashMap::new()),
+        slots: Arc::new(Semaphore::new(max_concurrent)),
+    });
+    tokio::spawn(evict_idle_sessions(Arc::downgrade(&state)));
+
+    Router::new()
+        .route("/mcp", post(handle_post).get(handle_get).delete(handle_delete))
+        .layer(middleware::from_fn_with_state(token.map(Arc::<str>::from), check_token))
+        .layer(middleware::from_fn(check_origin))
+        .with_state(state)
+}
+
+fn is_loopback(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Sweeps idle sessions until the router is dropped.
+async fn evict_idle_sessions(state: Weak<HttpState>) {
+    let idle = Duration::from_secs(get_session_idle_timeout());
+    let mut sweep = tokio::time::interval(idle.clamp(Duration::from_secs(1), Duration::from_secs(60)));
+    loop {
+        sweep.tick().await;
+        match state.upgrade() {
+            Some(state) => state.evict_idle(idle),
+            None => break,
+        }
+    }
+}
+
+/// Rejects requests a browser sends on behalf of another site, which is how
+/// DNS rebinding would reach a local server. Clients that are not browsers
+/// send no `Origin` and are let through.
+async fn check_origin(request: Request, next: Next) -> Response {
+    if let Some(origin) = request.headers().get(header::ORIGIN) {
+        if !origin.to_str().is_ok_and(origin_allowed) {
+            return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Rejects requests without the bearer token, if one is configured.
+async fn check_token(State(token): State<Option<Arc<str>>>, request: Request, next: Next) -> Response {
+    if let Some(token) = token {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if !presented.is_some_and(|presented| tokens_match(presented, &token)) {
+            return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], "Missing or invalid bearer token")
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Compares in time independent of where the tokens first differ.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Loopback origins, plus any listed in `MCP_ALLOWED_ORIGINS`.
+fn origin_allowed(origin: &str) -> bool {
+    let origin = origin.trim_end_matches('/');
+    if get_allowed_origins().iter().any(|allowed| allowed == origin) {
+        return true;
+    }
+
+    let Some((scheme, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    matches!(scheme, "http" | "https") && matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+async fn handle_post(State(state): State<Arc<HttpState>>, headers: HeaderMap, body: String) -> Response {
+    let request: Value = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(_) => {
+            let error = json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": {"code": -32700, "message": "Parse error"}
+            });
+            return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+        }
+    };
+
+    let (session_id, session) = if request.get("method").and_then(|m| m.as_str()) == Some("initialize") {
+        let id = Uuid::new_v4().to_string();
+        let session = Arc::new(state.server.new_session());
+        state.sessions.lock().unwrap().insert(id.clone(), Session {
+            server: session.clone(),
+            last_seen: Instant::now(),
+        });
+        info!("Opened MCP session {}", id);
+        (id, session)
+    } else {
+        match state.session(&headers) {
+            Ok(session) => session,
+            Err(rejection) => return rejection.into_response(),
+        }
+    };
+
+    match session.process_payload(request, Some(&state.slots)).await {
+        Some(response) => ([(SESSION_HEADER, session_id)], Json(response)).into_response(),
+        None => (StatusCode::ACCEPTED, [(SESSION_HEADER, session_id)]).into_response(),
+    }
+}
+
+async fn handle_get(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    let (_, session) = match state.session(&headers) {
+        Ok(session) => session,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    // One stream at a time; the lock is released when the client disconnects
+    // so it can reconnect
+    match session.outbound().try_lock_owned() {
+        Ok(outbound) => Sse::new(OutboundStream(outbound))
+            .keep_alive(KeepAlive::default())
+            .into_response(),
+        Err(_) => (StatusCode::CONFLICT, "An event stream is already open for this session").into_response(),
+    }
+}
+
+async fn handle_delete(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    match state.session(&headers) {
+        Ok((id, _)) => {
+            state.sessions.lock().unwrap().remove(&id);
+            info!("Closed MCP session {}", id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+/// A session's outbound messages as SSE events.
+struct OutboundStream(OwnedMutexGuard<Receiver<Value>>);
+
+impl Stream for OutboundStream {
+    type Item = Result<Event, Infallible>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0
+            .poll_recv(cx)
+            .map(|message| message.map(|m| Ok(Event::default().event("message").data(m.to_string()))))
+    }
+}
@@ -0,0 +1,80 @@
+use crate::notifications::NotifierHub;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::{Arc, OnceLock};
+
+// Sessions to forward log records to, once a server is up
+static HUB: OnceLock<Arc<NotifierHub>> = OnceLock::new();
+
+/// Logs to stderr through `env_logger` as before and also hands this
+/// crate's records to every session that asked for them with
+/// `logging/setLevel`. Stdout belongs to the protocol, so nothing here ever
+/// writes to it.
+struct McpLogger {
+    stderr: env_logger::Logger,
+}
+
+impl Log for McpLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata) || is_ours(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.stderr.matches(record) {
+            self.stderr.log(record);
+        }
+        if let Some(hub) = HUB.get() {
+            if is_ours(record.target()) {
+                hub.log(record.level(), record.target(), &record.args().to_string());
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+    }
+}
+
+// Dependencies log far too much to forward to clients
+fn is_ours(target: &str) -> bool {
+    target.starts_with(env!("CARGO_CRATE_NAME"))
+}
+
+/// Installs the logger. `RUST_LOG` still controls what reaches stderr.
+pub fn init() {
+    let stderr = env_logger::Builder::from_default_env()
+        .target(env_logger::Target::Stderr)
+        .build();
+    // Clients may ask for debug logs even when stderr is quieter
+    let max_level = stderr.filter().max(LevelFilter::Debug);
+
+    if log::set_boxed_logger(Box::new(McpLogger { stderr })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Starts forwarding log records to the sessions in `hub`.
+pub fn forward_to(hub: Arc<NotifierHub>) {
+    let _ = HUB.set(hub);
+}
+
+/// The MCP (RFC 5424) name for a log level.
+pub fn mcp_level(level: Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warn => "warning",
+        Level::Info => "info",
+        Level::Debug | Level::Trace => "debug",
+    }
+}
+
+/// The most verbose level to forward for an MCP level name, or `None` if
+/// the name is not one of the eight MCP levels.
+pub fn parse_mcp_level(level: &str) -> Option<LevelFilter> {
+    match level {
+        "debug" => Some(LevelFilter::Debug),
+        "info" | "notice" => Some(LevelFilter::Info),
+        "warning" => Some(LevelFilter::Warn),
+        "error" | "critical" | "alert" | "emergency" => Some(LevelFilter::Error),
+        _ => None,
+    }
+}
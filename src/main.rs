@@ -1,29 +1,42 @@
 // src/main.rs – fixed
+// Stdout carries the JSON-RPC stream; a stray print corrupts it.
+#![deny(clippy::print_stdout)]
 use anyhow::Result;
 use dotenv::dotenv;
-use env_logger;
 use log::{error, info};
 use std::env;
 
 // Top‑level crates / modules that really exist in this project.
 // All feature‑specific sub‑modules (database, heartbeat, memory, tasks, trello, …)
 // live under the `tools` crate, so we don’t declare them here to avoid E0583.
+mod backends;
+mod completions;
+mod http;
+mod logging;
+mod notifications;
+mod pagination;
+mod prompts;
+mod reaper;
+mod resources;
 mod server;
 mod schemas;
 mod tools;
 mod utils;
 
+#[cfg(test)]
+mod tests;
+
 use server::MCPServer;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables from .env file
     dotenv().ok();
 
-    // Initialise logging (writes to stderr by default so Warp can capture it)
-    env_logger::Builder::from_default_env()
-        .target(env_logger::Target::Stderr)
-        .init();
+    // Initialise logging (writes to stderr so Warp can capture it, and to
+    // MCP clients that ask for it with logging/setLevel)
+    logging::init();
 
     info!("Starting Warp MCP Tasks Server v1.0.0");
 
@@ -34,27 +47,55 @@ async fn main() -> Result<()> {
     // Spin‑up the MCP server and block until it terminates.
     let redis_url = env::var("REDIS_URL").unwrap();
     let redis_manager = utils::RedisManager::new(&redis_url).await?;    
-    let server = MCPServer::new(redis_manager).await?;
+    let backend = backends::from_env()?;
+    let server = Arc::new(MCPServer::new(redis_manager, backend).await?);
     info!("Redis URL: {}", redis_url);
     info!("Starting server...");
     info!("Server address: {}", server.address());
     info!("Server port: {}", server.port());
-    server.run().await; 
+    match utils::get_transport().as_str() {
+        "http" => http::serve(server).await?,
+        _ => server.run().await?,
+    }
     Ok(())      
-    // Note: The server.run() method is assumed to be an async method that runs
-    // the server indefinitely. If it returns, the server has stopped.
+    // Note: both transports run until the client side goes away (stdin EOF)
+    // or the listener fails. If they return, the server has stopped.
 }
 
 /// Ensures the process has all the variables it needs to operate.
 ///
-/// * `TRELLO_KEY`, `TRELLO_TOKEN`  and `TRELLO_BOARD_ID` are required – we bail
-///   out early if any of them are missing.
+/// * `TASK_BACKEND` picks the task tracker (`trello` by default, `local` for a
+///   directory of task files under `TASKS_DIR`, or `github` for the issues of
+///   `GITHUB_REPO` on any GitHub-compatible API at `GITHUB_API_URL`).
+/// * `TRELLO_KEY`, `TRELLO_TOKEN`  and `TRELLO_BOARD_ID` are required for the
+///   Trello backend, `GITHUB_REPO` and `GITHUB_TOKEN` for the GitHub one – we
+///   bail out early if any of them are missing.
 /// * `REDIS_URL` and `HEARTBEAT_TIMEOUT` are optional and get sane defaults if
-///   they are absent.
+///   they are absent. `LEASE_TIMEOUT` and `REAPER_INTERVAL` are derived from
+///   `HEARTBEAT_TIMEOUT` when unset.
+/// * `MCP_TRANSPORT` is `stdio` (default) or `http`; the HTTP transport
+///   listens on `MCP_HOST`:`MCP_PORT` (`127.0.0.1:8080` by default).
 fn validate_environment() -> Result<()> {
-    const REQUIRED_VARS: [&str; 3] = ["TRELLO_KEY", "TRELLO_TOKEN", "TRELLO_BOARD_ID"];
+    const TRELLO_VARS: [&str; 3] = ["TRELLO_KEY", "TRELLO_TOKEN", "TRELLO_BOARD_ID"];
+    const GITHUB_VARS: [&str; 2] = ["GITHUB_REPO", "GITHUB_TOKEN"];
+
+    let required: &[&str] = match utils::get_task_backend().as_str() {
+        "trello" => &TRELLO_VARS,
+        "local" => &[],
+        "github" => &GITHUB_VARS,
+        other => {
+            error!("Unknown TASK_BACKEND: {other}");
+            return Err(anyhow::anyhow!("Unknown TASK_BACKEND: {other}"));
+        }
+    };
+
+    let transport = utils::get_transport();
+    if transport != "stdio" && transport != "http" {
+        error!("Unknown MCP_TRANSPORT: {transport}");
+        return Err(anyhow::anyhow!("Unknown MCP_TRANSPORT: {transport}"));
+    }
 
-    for var in REQUIRED_VARS {        
+    for var in required {        
         if env::var(var).is_err() {
             error!("Missing required environment variable: {var}");
             return Err(anyhow::anyhow!("Missing environment variable: {var}"));
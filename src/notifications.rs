@@ -0,0 +1,258 @@
+use crate::utils::get_client_request_timeout;
+use anyhow::Result;
+use log::{Level, LevelFilter};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+
+/// Outbound side of the session: everything written to the client (responses,
+/// server-initiated notifications and requests) goes through here, and it
+/// remembers what the client can do, which resources it subscribed to and
+/// which log level it wants.
+pub struct Notifier {
+    outbound: Sender<Value>,
+    subscriptions: Mutex<HashSet<String>>,
+    // Off until the client asks for logs with `logging/setLevel`
+    log_level: Mutex<LevelFilter>,
+    // From the client's `initialize` request
+    client_capabilities: Mutex<Value>,
+    // Our requests awaiting a response, keyed by their serialized id
+    pending: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+    next_request_id: AtomicU64,
+}
+
+impl Notifier {
+    pub fn new(outbound: Sender<Value>) -> Self {
+        Self {
+            outbound,
+            subscriptions: Mutex::new(HashSet::new()),
+            log_level: Mutex::new(LevelFilter::Off),
+            client_capabilities: Mutex::new(json!({})),
+            pending: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Queues a message for the writer. Drops it if the queue is full (an
+    /// HTTP client that never opened its event stream) or the transport has
+    /// shut down, since there is nobody reading. Not logged: the log records
+    /// would come straight back here.
+    pub fn send(&self, message: Value) {
+        let _ = self.outbound.try_send(message);
+    }
+
+    /// Queues a message that must not be dropped, such as a response,
+    /// waiting for room if the writer is behind.
+    pub async fn send_reliably(&self, message: Value) {
+        let _ = self.outbound.send(message).await;
+    }
+
+    pub fn notify(&self, method: &str, params: Value) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params
+        }));
+    }
+
+    pub fn subscribe(&self, uri: &str) {
+        self.subscriptions.lock().unwrap().insert(uri.to_string());
+    }
+
+    pub fn unsubscribe(&self, uri: &str) {
+        self.subscriptions.lock().unwrap().remove(uri);
+    }
+
+    /// Sends `notifications/resources/updated` if the client subscribed to `uri`.
+    pub fn resource_updated(&self, uri: &str) {
+        if self.subscriptions.lock().unwrap().contains(uri) {
+            self.notify("notifications/resources/updated", json!({ "uri": uri }));
+        }
+    }
+
+    pub fn resource_list_changed(&self) {
+        self.notify("notifications/resources/list_changed", json!({}));
+    }
+
+    pub fn set_client_capabilities(&self, capabilities: Value) {
+        *self.client_capabilities.lock().unwrap() = capabilities;
+    }
+
+    /// Whether the client declared `capability` (e.g. `"sampling"`) when it
+    /// initialized.
+    pub fn client_supports(&self, capability: &str) -> bool {
+        !self.client_capabilities.lock().unwrap()[capability].is_null()
+    }
+
+    /// Sends a request to the client and waits for its `result`. Fails if
+    /// the request cannot be queued (nobody is reading the session), or the
+    /// client answers with an error or not within `CLIENT_REQUEST_TIMEOUT`
+    /// seconds.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = json!(format!("server-{}", self.next_request_id.fetch_add(1, Ordering::Relaxed)));
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.to_string(), tx);
+
+        let queued = self.outbound.try_send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        }));
+        if let Err(e) = queued {
+            self.pending.lock().unwrap().remove(&id.to_string());
+            let reason = match e {
+                TrySendError::Full(_) => "the outbound queue is full",
+                TrySendError::Closed(_) => "the session has closed",
+            };
+            return Err(anyhow::anyhow!("Could not send {} to the client: {}", method, reason));
+        }
+
+        let timeout = Duration::from_secs(get_client_request_timeout());
+        let response = tokio::time::timeout(timeout, rx).await;
+        self.pending.lock().unwrap().remove(&id.to_string());
+
+        let response = match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => return Err(anyhow::anyhow!("Session closed before the client answered {}", method)),
+            Err(_) => return Err(anyhow::anyhow!("Client did not answer {} within {:?}", method, timeout)),
+        };
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!(
+                "Client rejected {}: {}",
+                method,
+                error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error")
+            ));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Asks the user for input through `elicitation/create`. `schema` is the
+    /// flat object schema of the fields wanted. Returns the answer if the
+    /// user accepted, or `None` if they declined or dismissed the request.
+    pub async fn elicit(&self, message: &str, schema: Value) -> Result<Option<Value>> {
+        let result = self.request("elicitation/create", json!({
+            "message": message,
+            "requestedSchema": schema
+        })).await?;
+
+        match result.get("action").and_then(|a| a.as_str()) {
+            Some("accept") => Ok(Some(result.get("content").cloned().unwrap_or_else(|| json!({})))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Hands a client response to the request waiting for it. Returns false
+    /// if `message` is not a response at all.
+    pub fn resolve(&self, message: &Value) -> bool {
+        let is_response = message.get("method").is_none()
+            && (message.get("result").is_some() || message.get("error").is_some());
+        if !is_response {
+            return false;
+        }
+
+        let key = message.get("id").map(|id| id.to_string()).unwrap_or_default();
+        match self.pending.lock().unwrap().remove(&key) {
+            Some(waiter) => {
+                let _ = waiter.send(message.clone());
+            }
+            None => log::warn!("Dropping response to unknown request {}", key),
+        }
+        true
+    }
+
+    pub fn set_log_level(&self, level: LevelFilter) {
+        *self.log_level.lock().unwrap() = level;
+    }
+
+    /// Sends `notifications/message` if `level` is at or above the level the
+    /// client selected.
+    pub fn log(&self, level: Level, logger: &str, message: &str) {
+        if level <= *self.log_level.lock().unwrap() {
+            self.notify("notifications/message", json!({
+                "level": crate::logging::mcp_level(level),
+                "logger": logger,
+                "data": message
+            }));
+        }
+    }
+}
+
+/// Every live session's notifier, so a change made through one session (or
+/// by the reaper) reaches subscribers on all of them.
+pub struct NotifierHub {
+    notifiers: Mutex<Vec<Weak<Notifier>>>,
+}
+
+impl NotifierHub {
+    pub fn new() -> Self {
+        Self {
+            notifiers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn register(&self, notifier: &Arc<Notifier>) {
+        self.notifiers.lock().unwrap().push(Arc::downgrade(notifier));
+    }
+
+    fn each(&self, f: impl Fn(&Notifier)) {
+        let mut notifiers = self.notifiers.lock().unwrap();
+        // Drop sessions that have gone away while we are here
+        notifiers.retain(|n| n.strong_count() > 0);
+        for notifier in notifiers.iter().filter_map(Weak::upgrade) {
+            f(&notifier);
+        }
+    }
+
+    pub fn resource_updated(&self, uri: &str) {
+        self.each(|n| n.resource_updated(uri));
+    }
+
+    pub fn resource_list_changed(&self) {
+        self.each(|n| n.resource_list_changed());
+    }
+
+    pub fn log(&self, level: Level, logger: &str, message: &str) {
+        self.each(|n| n.log(level, logger, message));
+    }
+}
+
+impl Default for NotifierHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reports progress for one request if the client asked for it with a
+/// `progressToken`; otherwise every call is a no-op.
+pub struct Progress {
+    target: Option<(Arc<Notifier>, Value)>,
+}
+
+impl Progress {
+    pub fn new(notifier: Arc<Notifier>, token: Option<Value>) -> Self {
+        Self {
+            target: token.map(|token| (notifier, token)),
+        }
+    }
+
+    /// Sends `notifications/progress`; `progress` must increase between calls.
+    pub fn report(&self, progress: usize, total: Option<usize>, message: &str) {
+        if let Some((notifier, token)) = &self.target {
+            let mut params = json!({
+                "progressToken": token,
+                "progress": progress,
+                "message": message
+            });
+            if let Some(total) = total {
+                params["total"] = json!(total);
+            }
+            notifier.notify("notifications/progress", params);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// A cursor we did not hand out; maps to JSON-RPC code -32602.
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid cursor: {0}")]
+pub struct InvalidCursor(pub String);
+
+/// What a cursor points at. Clients only ever see it base64-encoded.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Position {
+    /// Resume after the item with this key. Used where we control the
+    /// ordering, so pages stay consistent while items come and go.
+    After(String),
+    /// Resume at this offset, for result sets ordered by someone else
+    /// (search ranking).
+    Offset(usize),
+    /// Resume after the item with this key in an order set by someone else
+    /// (a board's lists and card positions), or at this offset if that item
+    /// has gone.
+    AfterAt(String, usize),
+}
+
+impl Position {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|raw| serde_json::from_slice(&raw).ok())
+            .ok_or_else(|| InvalidCursor(cursor.to_string()).into())
+    }
+}
+
+/// One page of `items` in `key` order, starting after the item `cursor`
+/// points at. Returns the page and the cursor for the next one, if any.
+pub fn paginate<T>(
+    mut items: Vec<T>,
+    key: impl Fn(&T) -> String,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<(Vec<T>, Option<String>)> {
+    items.sort_by_cached_key(|item| key(item));
+
+    let start = match cursor.map(Position::decode).transpose()? {
+        None => 0,
+        Some(Position::After(last)) => items.partition_point(|item| key(item) <= last),
+        Some(_) => return Err(InvalidCursor(cursor.unwrap_or_default().to_string()).into()),
+    };
+
+    let mut page: Vec<T> = items.into_iter().skip(start).take(page_size + 1).collect();
+    let next_cursor = if page.len() > page_size {
+        page.truncate(page_size);
+        page.last().map(|item| Position::After(key(item)).encode())
+    } else {
+        None
+    };
+    Ok((page, next_cursor))
+}
+
+/// One page of `items` in the order given, for fixed lists whose order is
+/// meaningful (the tool registry). Resumes at the offset `cursor` holds.
+pub fn paginate_in_order<T>(items: Vec<T>, cursor: Option<&str>, page_size: usize) -> Result<(Vec<T>, Option<String>)> {
+    let start = offset(cursor)?;
+    let end = start.saturating_add(page_size);
+    let next_cursor = (end < items.len()).then(|| offset_cursor(end));
+    Ok((items.into_iter().skip(start).take(page_size).collect(), next_cursor))
+}
+
+/// One page of `items` in the order given, starting after the item with
+/// the `key` that `cursor` points at, so items added or dropped before it do
+/// not shift the page. Returns the page and the cursor for the next one, if
+/// any.
+pub fn paginate_after_in_order<T>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> String,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<(Vec<T>, Option<String>)> {
+    let start = match cursor.map(Position::decode).transpose()? {
+        None => 0,
+        Some(Position::AfterAt(last, offset)) => items
+            .iter()
+            .position(|item| key(item) == last)
+            .map_or(offset, |index| index + 1),
+        Some(_) => return Err(InvalidCursor(cursor.unwrap_or_default().to_string()).into()),
+    };
+
+    let end = start.saturating_add(page_size);
+    let next_cursor = if end < items.len() {
+        items.get(end - 1).map(|last| Position::AfterAt(key(last), end).encode())
+    } else {
+        None
+    };
+    Ok((items.into_iter().skip(start).take(page_size).collect(), next_cursor))
+}
+
+/// The offset an offset cursor resumes at (0 without a cursor).
+pub fn offset(cursor: Option<&str>) -> Result<usize> {
+    match cursor.map(Position::decode).transpose()? {
+        None => Ok(0),
+        Some(Position::Offset(offset)) => Ok(offset),
+        Some(_) => Err(InvalidCursor(cursor.unwrap_or_default().to_string()).into()),
+    }
+}
+
+/// A cursor resuming at `offset`.
+pub fn offset_cursor(offset: usize) -> String {
+    Position::Offset(offset).encode()
+}
@@ -0,0 +1,162 @@
+use crate::backends::TaskBackend;
+use crate::schemas::{ScannedCard, SearchKnowledgeArgs};
+use crate::tools::{assignment, memory, tasks};
+use crate::utils::{RedisManager, get_heartbeat_timeout, get_lease_timeout, get_page_size};
+use anyhow::Result;
+use redis::AsyncCommands;
+use serde_json::{json, Value};
+
+/// Unknown prompt or missing argument; maps to JSON-RPC code -32602.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidPromptRequest(pub String);
+
+pub fn list_prompts() -> Value {
+    json!([
+        {
+            "name": "pick_up_next_task",
+            "description": "Choose an available task from the board, claim it and start the heartbeat loop",
+            "arguments": [
+                {"name": "agent_id", "description": "Your agent identifier", "required": true}
+            ]
+        },
+        {
+            "name": "write_handoff_note",
+            "description": "Write a handoff note for a task so another agent can continue it",
+            "arguments": [
+                {"name": "agent_id", "description": "Your agent identifier", "required": true},
+                {"name": "card_id", "description": "The task being handed off", "required": true}
+            ]
+        },
+        {
+            "name": "summarize_learnings",
+            "description": "Summarize what agents have learned about a topic",
+            "arguments": [
+                {"name": "topic", "description": "Topic, API or error to summarize", "required": true}
+            ]
+        }
+    ])
+}
+
+/// Renders prompt `name`, pre-filled with live board and knowledge data.
+pub async fn get_prompt(
+    redis: &RedisManager,
+    backend: &dyn TaskBackend,
+    name: &str,
+    arguments: &Value,
+) -> Result<Value> {
+    let (description, text) = match name {
+        "pick_up_next_task" => {
+            let agent_id = required_arg(arguments, "agent_id")?;
+            let available = available_tasks(redis, backend).await?;
+
+            (
+                "Pick up the next available task",
+                format!(
+                    "You are agent `{agent_id}`. These tasks are in the todo list and not claimed by anyone:\n\n{}\n\n\
+                     Pick the one you are best placed to do and claim it with `take_trello_task`. Keep the \
+                     `lease_token` it returns: every `update_trello_task`, `release_trello_task` and \
+                     `complete_trello_task` call needs it. Send a `heartbeat` for the card at least every {} \
+                     seconds while you work; after {} seconds without one the lease lapses and the task \
+                     goes back on the board. \
+                     Before starting, use `search_knowledge` to check what other agents already learned \
+                     about the area.",
+                    serde_json::to_string_pretty(&available)?,
+                    get_heartbeat_timeout(),
+                    get_lease_timeout()
+                ),
+            )
+        }
+        "write_handoff_note" => {
+            let agent_id = required_arg(arguments, "agent_id")?;
+            let card_id = required_arg(arguments, "card_id")?;
+            let card = backend.get_card(card_id).await?;
+            let history = update_history(redis, card_id).await?;
+
+            (
+                "Write a handoff note for a task",
+                format!(
+                    "You are agent `{agent_id}` and are handing off task `{card_id}` ({}).\n\n\
+                     Description:\n{}\n\nUpdate history:\n{}\n\n\
+                     Write a handoff note covering what is done, what is left, open questions and any \
+                     gotchas. Post it with `update_trello_task` (update_type `comment`), store it with \
+                     `store_knowledge` under category `task_progress`, then call `release_trello_task`.",
+                    card.name,
+                    card.description,
+                    serde_json::to_string_pretty(&history)?
+                ),
+            )
+        }
+        "summarize_learnings" => {
+            let topic = required_arg(arguments, "topic")?;
+            let search = SearchKnowledgeArgs {
+                query: topic.to_string(),
+                limit: Some(20),
+                ..Default::default()
+            };
+            let results = memory::search_knowledge(redis, search).await?;
+
+            (
+                "Summarize what agents learned about a topic",
+                format!(
+                    "Here is everything agents stored about \"{topic}\":\n\n{}\n\n\
+                     Summarize the common patterns, the errors that came up with the solutions that worked, \
+                     and anything that is still unresolved.",
+                    serde_json::to_string_pretty(&results.results)?
+                ),
+            )
+        }
+        other => return Err(InvalidPromptRequest(format!("Unknown prompt: {}", other)).into()),
+    };
+
+    Ok(json!({
+        "description": description,
+        "messages": [{
+            "role": "user",
+            "content": {"type": "text", "text": text}
+        }]
+    }))
+}
+
+/// Up to a page of todo cards nobody has claimed. The board is fetched
+/// once and every assignment looked up in one MGET, however far down the
+/// list the free cards are.
+async fn available_tasks(redis: &RedisManager, backend: &dyn TaskBackend) -> Result<Vec<ScannedCard>> {
+    let todo = backend.workflow_lists().todo;
+    let mut cards = backend.list_open_cards().await?;
+    cards.retain(|card| !card.closed && card.list_id == todo);
+    if cards.is_empty() {
+        return Ok(Vec::new());
+    }
+    let lists = backend.list_lists().await?;
+
+    let mut conn = redis.get_connection().await?;
+    let keys: Vec<String> = cards.iter().map(|card| assignment::assignment_key(&card.id)).collect();
+    let claims: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(&mut conn).await?;
+
+    Ok(cards
+        .into_iter()
+        .zip(claims)
+        .filter(|(_, claim)| claim.is_none())
+        .map(|(card, _)| tasks::scanned_card(card, &lists, None))
+        .take(get_page_size())
+        .collect())
+}
+
+fn required_arg<'a>(arguments: &'a Value, name: &str) -> Result<&'a str> {
+    arguments
+        .get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| InvalidPromptRequest(format!("Missing required argument: {}", name)).into())
+}
+
+/// The recorded update history for a card, oldest first.
+async fn update_history(redis: &RedisManager, card_id: &str) -> Result<Vec<Value>> {
+    let mut conn = redis.get_connection().await?;
+    let raw: Vec<String> = conn.lrange(tasks::updates_key(card_id), 0, -1).await?;
+
+    Ok(raw
+        .iter()
+        .filter_map(|update| serde_json::from_str::<Value>(update).ok())
+        .collect())
+}
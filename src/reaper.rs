@@ -0,0 +1,79 @@
+use crate::backends::TaskBackend;
+use crate::notifications::NotifierHub;
+use crate::resources;
+use crate::tools::{assignment, tasks};
+use crate::utils::{RedisManager, get_lease_timeout, get_reaper_interval};
+use anyhow::Result;
+use log::{error, info, warn};
+use redis::AsyncCommands;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+
+/// Periodically hands cards held by dead agents back to the todo list. An
+/// agent counts as dead once its lease lapsed, i.e. no heartbeat renewed it
+/// for `LEASE_TIMEOUT` seconds.
+///
+/// Runs forever; spawned once per process by `MCPServer::spawn_reaper`.
+pub async fn run(redis: RedisManager, backend: Arc<dyn TaskBackend>, notifier: Arc<NotifierHub>) {
+    let mut ticker = interval(Duration::from_secs(get_reaper_interval()));
+    info!("Stale-assignment reaper running every {}s", get_reaper_interval());
+
+    loop {
+        ticker.tick().await;
+        match reap_stale_assignments(&redis, backend.as_ref(), &notifier).await {
+            Ok(0) => {}
+            Ok(count) => info!("Reclaimed {} stale task assignment(s)", count),
+            Err(e) => error!("Stale-assignment sweep failed: {}", e),
+        }
+    }
+}
+
+/// One sweep over the assignment index. Returns how many cards were reclaimed.
+pub async fn reap_stale_assignments(
+    redis: &RedisManager,
+    backend: &dyn TaskBackend,
+    notifier: &NotifierHub,
+) -> Result<usize> {
+    let mut conn = redis.get_connection().await?;
+    let claims: HashMap<String, String> = conn.hgetall(assignment::ASSIGNMENTS_INDEX).await?;
+
+    let timeout = get_lease_timeout();
+    let todo_list_id = backend.workflow_lists().todo;
+    let mut reclaimed = 0;
+
+    for (card_id, agent_id) in claims {
+        if !assignment::reap(&mut conn, &card_id, &agent_id).await? {
+            continue;
+        }
+        reclaimed += 1;
+        warn!("Reclaimed task {} from unresponsive agent {}", card_id, agent_id);
+
+        // The claim is already gone from Redis; backend failures only affect
+        // where the card sits on the board, so log and carry on.
+        if let Err(e) = backend.move_card(&card_id, &todo_list_id).await {
+            error!("Failed to move reclaimed task {} back to todo: {}", card_id, e);
+        }
+        let comment = format!(
+            "Task reclaimed from agent {}: no heartbeat received for over {} seconds. The card is available again.",
+            agent_id, timeout
+        );
+        if let Err(e) = backend.add_comment(&card_id, &comment).await {
+            error!("Failed to comment on reclaimed task {}: {}", card_id, e);
+        }
+
+        tasks::record_update(&mut conn, &card_id, json!({
+            "agent_id": agent_id,
+            "type": "reclaim",
+            "outcome": "reclaimed",
+            "content": comment,
+            "timestamp": chrono::Utc::now().timestamp()
+        })).await?;
+
+        notifier.resource_updated(&resources::card_uri(&card_id));
+        notifier.resource_updated(resources::ACTIVE_AGENTS_URI);
+    }
+
+    Ok(reclaimed)
+}
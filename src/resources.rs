@@ -0,0 +1,103 @@
+use crate::backends::TaskBackend;
+use crate::tools::{assignment, heartbeat, memory};
+use crate::utils::RedisManager;
+use anyhow::Result;
+use serde_json::{json, Value};
+
+pub const CARD_PREFIX: &str = "trello://card/";
+pub const KNOWLEDGE_PREFIX: &str = "knowledge://";
+pub const ACTIVE_AGENTS_URI: &str = "agents://active";
+
+const JSON_MIME: &str = "application/json";
+
+/// Error for a URI we do not serve; maps to JSON-RPC code -32002.
+#[derive(Debug, thiserror::Error)]
+#[error("Resource not found: {0}")]
+pub struct ResourceNotFound(pub String);
+
+pub fn card_uri(card_id: &str) -> String {
+    format!("{}{}", CARD_PREFIX, card_id)
+}
+
+pub fn knowledge_uri(id: &str) -> String {
+    format!("{}{}", KNOWLEDGE_PREFIX, id)
+}
+
+pub fn resource_templates() -> Value {
+    json!([
+        {
+            "uriTemplate": format!("{}{{card_id}}", CARD_PREFIX),
+            "name": "Task card",
+            "description": "A card on the task board with its current assignment",
+            "mimeType": JSON_MIME
+        },
+        {
+            "uriTemplate": format!("{}{{id}}", KNOWLEDGE_PREFIX),
+            "name": "Knowledge entry",
+            "description": "A knowledge entry stored by an agent",
+            "mimeType": JSON_MIME
+        }
+    ])
+}
+
+/// Every card, every knowledge entry and the active-agents view.
+pub async fn list_resources(redis: &RedisManager, backend: &dyn TaskBackend) -> Result<Vec<Value>> {
+    let mut resources = vec![json!({
+        "uri": ACTIVE_AGENTS_URI,
+        "name": "Active agents",
+        "description": "Agents with a live heartbeat and what they are working on",
+        "mimeType": JSON_MIME
+    })];
+
+    for card in backend.list_cards().await? {
+        resources.push(json!({
+            "uri": card_uri(&card.id),
+            "name": card.name,
+            "description": format!("Task card in list {}", card.list_id),
+            "mimeType": JSON_MIME
+        }));
+    }
+
+    for entry in memory::all_knowledge(redis).await? {
+        resources.push(json!({
+            "uri": knowledge_uri(&entry.id),
+            "name": entry.key,
+            "description": format!("{} knowledge from agent {}", entry.category, entry.agent_id),
+            "mimeType": JSON_MIME
+        }));
+    }
+
+    Ok(resources)
+}
+
+/// Reads `uri`, returning the MCP `contents` array.
+pub async fn read_resource(redis: &RedisManager, backend: &dyn TaskBackend, uri: &str) -> Result<Value> {
+    let text = if let Some(card_id) = uri.strip_prefix(CARD_PREFIX) {
+        let card = backend
+            .get_card(card_id)
+            .await
+            .map_err(|_| ResourceNotFound(uri.to_string()))?;
+        let mut conn = redis.get_connection().await?;
+        let assigned_to = assignment::get(&mut conn, card_id).await?.map(|a| a.agent_id);
+
+        json!({
+            "card": card,
+            "assigned_to": assigned_to
+        }).to_string()
+    } else if let Some(id) = uri.strip_prefix(KNOWLEDGE_PREFIX) {
+        let entry = memory::get_knowledge(redis, id)
+            .await?
+            .ok_or_else(|| ResourceNotFound(uri.to_string()))?;
+        serde_json::to_string(&entry)?
+    } else if uri == ACTIVE_AGENTS_URI {
+        serde_json::to_string(&heartbeat::check_agent_status(redis).await?)?
+    } else {
+        return Err(ResourceNotFound(uri.to_string()).into());
+    };
+
+    Ok(json!([{
+        "uri": uri,
+        "mimeType": JSON_MIME,
+        "text": text
+    }]))
+}
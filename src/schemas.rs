@@ -1,6 +1,11 @@
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
+#[allow(dead_code)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TrelloBadges {
     pub attachments: i32,
@@ -25,43 +30,189 @@ pub struct TrelloBadges {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ToolCall {
     pub name: String,
+    #[serde(default)]
     pub arguments: Value,
+    #[serde(rename = "_meta", default)]
+    pub meta: Option<RequestMeta>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+pub struct RequestMeta {
+    #[serde(rename = "progressToken")]
+    pub progress_token: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ListFilter {
+    Todo,
+    InProgress,
+    Done,
+    All,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ScanTasksArgs {
+    /// Filter cards by list (default all). Closed issues on an issue tracker
+    /// are only listed for done
+    pub list_filter: Option<ListFilter>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    /// Maximum number of cards per page (default PAGE_SIZE)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct TakeTaskArgs {
+    /// Unique agent identifier
     pub agent_id: String,
+    /// Card ID to claim
     pub card_id: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateType {
+    Comment,
+    Checklist,
+    Description,
+    MoveList,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct UpdateTaskArgs {
     pub agent_id: String,
     pub card_id: String,
-    pub update_type: String,
+    pub update_type: UpdateType,
     pub content: String,
+    /// For move_list only; the server asks for it when left out and the
+    /// client supports elicitation
     pub list_id: Option<String>,
+    /// Lease token returned by take_trello_task
+    pub lease_token: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ReleaseTaskArgs {
+    pub agent_id: String,
+    pub card_id: String,
+    pub lease_token: String,
+    /// Why the task is being released
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CompleteTaskArgs {
+    pub agent_id: String,
+    pub card_id: String,
+    pub lease_token: String,
+    pub summary: String,
+    #[serde(default = "default_success")]
+    pub success: bool,
+    /// Why the task failed; required when success is false
+    pub failure_reason: Option<String>,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+pub const KNOWLEDGE_CATEGORIES: [&str; 6] = [
+    "task_progress",
+    "api_docs",
+    "code_patterns",
+    "errors",
+    "solutions",
+    "project_knowledge",
+];
+
+fn knowledge_category_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = String::json_schema(gen).into_object();
+    schema.enum_values = Some(KNOWLEDGE_CATEGORIES.iter().map(|c| Value::from(*c)).collect());
+    schema.into()
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct StoreKnowledgeArgs {
     pub agent_id: String,
+    #[schemars(schema_with = "knowledge_category_schema")]
     pub category: String,
     pub key: String,
     pub content: String,
+    /// RAG search tags
     pub tags: Vec<String>,
     pub metadata: Option<Value>,
+    /// Replace the entry already stored under this category and key instead
+    /// of adding another (default false)
+    pub upsert: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct UpdateKnowledgeArgs {
+    pub agent_id: String,
+    /// Knowledge entry ID
+    pub id: String,
+    /// New content; left unchanged when omitted
+    pub content: Option<String>,
+    /// New RAG search tags; left unchanged when omitted
+    pub tags: Option<Vec<String>>,
+    /// New metadata; left unchanged when omitted
+    pub metadata: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct KnowledgeHistoryArgs {
+    /// Knowledge entry ID
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RevertKnowledgeArgs {
+    pub agent_id: String,
+    /// Knowledge entry ID
+    pub id: String,
+    /// Version to restore, as listed by get_knowledge_history
+    pub version: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DeleteKnowledgeArgs {
+    /// Must be the agent that stored the entry, or a knowledge admin
+    pub agent_id: String,
+    /// Knowledge entry ID
+    pub id: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
 pub struct SearchKnowledgeArgs {
     pub query: String,
     pub category_filter: Option<String>,
     pub agent_filter: Option<String>,
+    /// Maximum number of results (default 10)
     pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeRange {
+    Hour,
+    Day,
+    Week,
+    All,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct LearnFromAgentsArgs {
+    pub topic: String,
+    /// Error message or pattern to narrow the search to
+    pub error_pattern: Option<String>,
+    /// Only learn from entries stored or updated this recently (default all)
+    pub time_range: Option<TimeRange>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct HeartbeatArgs {
     pub agent_id: String,
     pub card_id: String,
@@ -69,6 +220,211 @@ pub struct HeartbeatArgs {
     pub progress_percentage: Option<f32>,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CheckAgentStatusArgs {}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RagQueryArgs {
+    /// Substring to look for in the selected JSON
+    pub query: String,
+    /// RedisJSON path to query in every knowledge entry (default `$`)
+    pub json_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CardStatus {
+    Available,
+    Assigned,
+}
+
+/// A card as reported by `scan_trello_tasks`, with its list and assignment.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ScannedCard {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub list_id: String,
+    pub list_name: String,
+    pub status: CardStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    pub url: String,
+    pub short_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    pub due_complete: bool,
+    pub labels: Vec<TaskLabel>,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ScanTasksResult {
+    pub cards: Vec<ScannedCard>,
+    /// Matching cards across all pages
+    pub total: usize,
+    pub lists: Vec<TaskList>,
+    pub filters_applied: ListFilter,
+    /// Pass as `cursor` to fetch the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct TakeTaskResult {
+    pub message: String,
+    pub card_id: String,
+    pub agent_id: String,
+    /// Pass this to every later update, release or complete call
+    pub lease_token: String,
+    /// Seconds until the lease lapses without a heartbeat
+    pub expires_in: u64,
+}
+
+/// Outcome of an update or release on a claimed card.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct TaskActionResult {
+    pub message: String,
+    pub card_id: String,
+    pub agent_id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CompleteTaskResult {
+    pub message: String,
+    pub card_id: String,
+    pub agent_id: String,
+    pub success: bool,
+    /// The list the card was moved to
+    pub list_id: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct StoreKnowledgeResult {
+    pub message: String,
+    pub id: String,
+    /// Whether an existing entry was replaced rather than a new one added
+    pub updated: bool,
+}
+
+/// Outcome of an update, revert or delete on a knowledge entry.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct KnowledgeActionResult {
+    pub message: String,
+    pub id: String,
+}
+
+/// One version of a knowledge entry as it was written.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct KnowledgeVersion {
+    pub version: u32,
+    /// Agent that wrote this version
+    pub agent_id: String,
+    pub timestamp: i64,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub metadata: Value,
+    /// What changed since the previous version; empty for the first
+    pub diff: KnowledgeDiff,
+    /// Set when this version restored an earlier one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverted_from: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct KnowledgeDiff {
+    /// Content lines that were not there before
+    pub content_added: Vec<String>,
+    /// Content lines that are gone
+    pub content_removed: Vec<String>,
+    pub tags_added: Vec<String>,
+    pub tags_removed: Vec<String>,
+    /// Top-level metadata keys that were added, removed or changed
+    pub metadata_changed: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct KnowledgeHistoryResult {
+    pub id: String,
+    pub current_version: u32,
+    /// Oldest first
+    pub versions: Vec<KnowledgeVersion>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SearchKnowledgeResult {
+    pub query: String,
+    /// Matching knowledge entries
+    pub results: Vec<Value>,
+    pub count: usize,
+    /// Pass as `cursor` to fetch the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct LearningsResult {
+    pub topic: String,
+    /// Entries grouped by the agent that stored them
+    pub agent_learnings: HashMap<String, Vec<Value>>,
+    pub common_patterns: Vec<Value>,
+    pub error_solutions: Vec<Value>,
+    /// Model that distilled the patterns, when the client supports sampling;
+    /// otherwise the raw matching entries are returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distilled_by: Option<String>,
+}
+
+/// What we ask the client's model to turn matched knowledge into.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DistilledLearnings {
+    #[serde(default)]
+    pub common_patterns: Vec<Value>,
+    #[serde(default)]
+    pub error_solutions: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct HeartbeatResult {
+    pub message: String,
+    pub agent_id: String,
+    pub card_id: String,
+    /// Whether the agent held the card's lease and it was extended
+    pub lease_renewed: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ActiveAgent {
+    pub agent_id: String,
+    pub card_id: String,
+    pub status: String,
+    pub progress: f32,
+    pub last_seen: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct AgentStatusResult {
+    pub active_agents: Vec<ActiveAgent>,
+    pub total_active: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RagMatch {
+    pub key: String,
+    /// The JSON selected by the path, serialized
+    #[serde(rename = "match")]
+    pub matched: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RagQueryResult {
+    pub query: String,
+    pub path: String,
+    pub results: Vec<RagMatch>,
+    pub count: usize,
+}
+
+#[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TrelloCard {
     pub id: String,
@@ -116,6 +472,7 @@ pub struct TrelloLabel {
     pub color: Option<String>,
 }
 
+#[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TrelloList {
     pub id: String,
@@ -128,6 +485,79 @@ pub struct TrelloList {
     pub nodeId: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GitHubIssue {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub state: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    pub assignees: Vec<GitHubUser>,
+    pub pull_request: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GitHubLabel {
+    pub id: u64,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GitHubUser {
+    pub login: String,
+}
+
+/// Backend-neutral view of a task card, as returned by a `TaskBackend`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TaskCard {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub list_id: String,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub short_url: String,
+    pub due: Option<String>,
+    #[serde(default)]
+    pub due_complete: bool,
+    #[serde(default)]
+    pub labels: Vec<TaskLabel>,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct TaskLabel {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// A column/list/state that cards live in.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct TaskList {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub closed: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TaskAssignment {
+    pub agent_id: String,
+    pub lease_token: String,
+    pub claimed_at: i64,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AgentStatus {
     pub agent_id: String,
@@ -138,7 +568,7 @@ pub struct AgentStatus {
     pub progress: f32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct KnowledgeEntry {
     pub id: String,
     pub agent_id: String,
@@ -150,4 +580,12 @@ pub struct KnowledgeEntry {
     pub created_at: i64,
     pub updated_at: i64,
     pub access_count: u32,
+    /// Counts up from 1 with every change; entries stored before versioning
+    /// read as version 1
+    #[serde(default = "first_version")]
+    pub version: u32,
+}
+
+fn first_version() -> u32 {
+    1
 }
\ No newline at end of file
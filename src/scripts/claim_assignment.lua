@@ -0,0 +1,12 @@
+-- Creates a task assignment and records it in the assignments index, but
+-- only if the card is not already held
+-- KEYS[1] = assignment:{card_id}, KEYS[2] = assignments index
+-- ARGV[1] = assignment JSON, ARGV[2] = lease duration in seconds,
+-- ARGV[3] = card id, ARGV[4] = agent id
+
+if not redis.call('SET', KEYS[1], ARGV[1], 'NX', 'EX', tonumber(ARGV[2])) then
+    return 0
+end
+
+redis.call('HSET', KEYS[2], ARGV[3], ARGV[4])
+return 1
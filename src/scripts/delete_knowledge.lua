@@ -0,0 +1,24 @@
+-- Deletes a knowledge entry with its history and drops it from the
+-- (category, key) index, but only for its author or a knowledge admin
+-- KEYS[1] = knowledge:{id}, KEYS[2] = knowledge_keys index, KEYS[3] = knowledge_history:{id}
+-- ARGV[1] = entry id, ARGV[2] = calling agent id, ARGV[3] = '1' if the
+-- caller is a knowledge admin
+-- Returns nil if there is no such entry, otherwise {deleted (0 or 1), author}
+
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return nil
+end
+
+local entry = cjson.decode(redis.call('JSON.GET', KEYS[1], '$'))[1]
+if entry.agent_id ~= ARGV[2] and ARGV[3] ~= '1' then
+    return {0, entry.agent_id}
+end
+
+redis.call('DEL', KEYS[1], KEYS[3])
+
+-- Leave the index alone if a duplicate has since taken over the key
+local field = entry.category .. ':' .. entry.key
+if redis.call('HGET', KEYS[2], field) == ARGV[1] then
+    redis.call('HDEL', KEYS[2], field)
+end
+return {1, entry.agent_id}
@@ -0,0 +1,16 @@
+-- Forgets a task assignment whose lease lapsed without being renewed
+-- KEYS[1] = assignment:{card_id}, KEYS[2] = assignments index
+-- ARGV[1] = card id, ARGV[2] = agent id recorded in the index
+
+-- Someone else already re-claimed (or released) the card
+if redis.call('HGET', KEYS[2], ARGV[1]) ~= ARGV[2] then
+    return 0
+end
+
+-- The lease is still live; its owner has until it lapses to heartbeat
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    return 0
+end
+
+redis.call('HDEL', KEYS[2], ARGV[1])
+return 1
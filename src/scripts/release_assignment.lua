@@ -0,0 +1,17 @@
+-- Deletes a task assignment, but only if the caller presents the current lease
+-- KEYS[1] = assignment:{card_id}, KEYS[2] = assignments index
+-- ARGV[1] = agent id, ARGV[2] = lease token, ARGV[3] = card id
+
+local raw = redis.call('GET', KEYS[1])
+if not raw then
+    return 0
+end
+
+local assignment = cjson.decode(raw)
+if assignment.agent_id ~= ARGV[1] or assignment.lease_token ~= ARGV[2] then
+    return 0
+end
+
+redis.call('DEL', KEYS[1])
+redis.call('HDEL', KEYS[2], ARGV[3])
+return 1
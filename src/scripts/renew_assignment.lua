@@ -0,0 +1,16 @@
+-- Extends a task assignment lease, but only if it is still held by the caller
+-- KEYS[1] = assignment:{card_id}
+-- ARGV[1] = agent id, ARGV[2] = lease duration in seconds
+
+local raw = redis.call('GET', KEYS[1])
+if not raw then
+    return 0
+end
+
+local assignment = cjson.decode(raw)
+if assignment.agent_id ~= ARGV[1] then
+    return 0
+end
+
+redis.call('EXPIRE', KEYS[1], tonumber(ARGV[2]))
+return 1
@@ -0,0 +1,30 @@
+-- Takes a task assignment away from an owner that stopped sending heartbeats,
+-- before its lease lapses
+-- KEYS[1] = assignment:{card_id}, KEYS[2] = assignments index,
+-- KEYS[3] = heartbeat:{agent_id}:{card_id}
+-- ARGV[1] = card id, ARGV[2] = agent id recorded in the index,
+-- ARGV[3] = claims newer than this timestamp are still in their grace period
+
+-- Someone else already re-claimed (or released) the card
+if redis.call('HGET', KEYS[2], ARGV[1]) ~= ARGV[2] then
+    return 0
+end
+
+local raw = redis.call('GET', KEYS[1])
+if raw then
+    local assignment = cjson.decode(raw)
+    if assignment.agent_id ~= ARGV[2] then
+        return 0
+    end
+    if redis.call('EXISTS', KEYS[3]) == 1 then
+        return 0
+    end
+    if assignment.claimed_at > tonumber(ARGV[3]) then
+        return 0
+    end
+    redis.call('DEL', KEYS[1])
+end
+
+-- Either the lease already lapsed or we just dropped it
+redis.call('HDEL', KEYS[2], ARGV[1])
+return 1
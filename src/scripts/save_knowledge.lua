@@ -0,0 +1,28 @@
+-- Writes a knowledge entry and appends it to its history, but only if the
+-- entry is still at the version the caller read it at
+-- KEYS[1] = knowledge:{id}, KEYS[2] = knowledge_keys index, KEYS[3] = knowledge_history:{id}
+-- ARGV[1] = expected version (0 for an entry that must not exist yet),
+-- ARGV[2] = entry JSON, ARGV[3] = index field, ARGV[4] = entry id,
+-- ARGV[5] = expiry in seconds, ARGV[6] = version to record first when the
+-- history is empty ('' for none), ARGV[7] = version being written
+
+local current = 0
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    -- Entries stored before versioning read as version 1
+    current = cjson.decode(redis.call('JSON.GET', KEYS[1], '$.version'))[1] or 1
+end
+if current ~= tonumber(ARGV[1]) then
+    return 0
+end
+
+redis.call('JSON.SET', KEYS[1], '$', ARGV[2])
+redis.call('EXPIRE', KEYS[1], ARGV[5])
+redis.call('HSET', KEYS[2], ARGV[3], ARGV[4])
+
+if ARGV[6] ~= '' and redis.call('LLEN', KEYS[3]) == 0 then
+    redis.call('RPUSH', KEYS[3], ARGV[6])
+end
+redis.call('RPUSH', KEYS[3], ARGV[7])
+-- History lives exactly as long as the entry
+redis.call('EXPIRE', KEYS[3], ARGV[5])
+return 1
@@ -1,76 +1,426 @@
+use crate::backends::TaskBackend;
+use crate::notifications::{Notifier, NotifierHub, Progress};
+use crate::pagination::{self, InvalidCursor};
+use crate::completions::{self, InvalidCompletionRequest};
+use crate::logging;
+use crate::prompts::{self, InvalidPromptRequest};
+use crate::reaper;
+use crate::resources::{self, ResourceNotFound};
 use crate::schemas::*;
-use crate::tools::{database, heartbeat, memory, tasks};
-use crate::utils::RedisManager;
+use crate::tools::registry::{InvalidArguments, ToolContext, ToolRegistry, UnknownTool};
+use crate::utils::{RedisManager, get_http_address, get_max_concurrent_requests, get_max_queued_requests, get_outbound_buffer, get_page_size};
 use anyhow::Result;
 use log::{error, info};
 use serde_json::{json, Value};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, Receiver};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
+use tokio::task::{AbortHandle, JoinHandle};
 
+/// MCP protocol revisions we speak, newest first.
+const SUPPORTED_PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// One MCP session with a client. The stdio transport runs a single session;
+/// the HTTP transport creates one per client with [`MCPServer::new_session`],
+/// all sharing the same Redis, task backend and notifier hub.
 pub struct MCPServer {
     redis: RedisManager,
-    trello_client: reqwest::Client,
+    backend: Arc<dyn TaskBackend>,
+    tools: Arc<ToolRegistry>,
+    hub: Arc<NotifierHub>,
+    notifier: Arc<Notifier>,
+    // Messages queued for the client; whoever holds the lock is the writer
+    outbound: Arc<AsyncMutex<Receiver<Value>>>,
+    // Requests keyed by their serialized JSON-RPC id from the moment they are
+    // read; `None` until they start, and for those that must run to
+    // completion even when cancelled
+    in_flight: Mutex<HashMap<String, Option<AbortHandle>>>,
     address: String,
     port: u16,
 }
 
 impl MCPServer {
-    pub async fn new(redis: RedisManager) -> Result<Self> {
-        let trello_client = reqwest::Client::new();
-
+    pub async fn new(redis: RedisManager, backend: Arc<dyn TaskBackend>) -> Result<Self> {
         info!("MCP Server initialized with enhanced database capabilities");
+        info!("Task backend: {}", backend.name());
+        
+        let (address, port) = get_http_address();
+        let tools = Arc::new(ToolRegistry::builtin());
+        let hub = Arc::new(NotifierHub::new());
+        logging::forward_to(hub.clone());
+        Ok(Self::session(redis, backend, tools, hub, address, port))
+    }
+
+    fn session(
+        redis: RedisManager,
+        backend: Arc<dyn TaskBackend>,
+        tools: Arc<ToolRegistry>,
+        hub: Arc<NotifierHub>,
+        address: String,
+        port: u16,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(get_outbound_buffer());
+        let notifier = Arc::new(Notifier::new(tx));
         
-        Ok(Self {
+        Self {
             redis,
-            trello_client,
-            address: String::from("127.0.0.1"),
-            port: 8080,
-        })
+            backend,
+            tools,
+            hub,
+            notifier,
+            outbound: Arc::new(AsyncMutex::new(rx)),
+            in_flight: Mutex::new(HashMap::new()),
+            address,
+            port,
+        }
+    }
+
+    /// A server offering `tools` instead of the built-in ones.
+    #[cfg(test)]
+    pub(crate) fn with_tools(redis: RedisManager, backend: Arc<dyn TaskBackend>, tools: ToolRegistry) -> Self {
+        Self::session(redis, backend, Arc::new(tools), Arc::new(NotifierHub::new()), "127.0.0.1".to_string(), 0)
+    }
+
+    /// A fresh session sharing this server's Redis, backend and notifier hub.
+    pub fn new_session(&self) -> Self {
+        let session = Self::session(
+            self.redis.clone(),
+            self.backend.clone(),
+            self.tools.clone(),
+            self.hub.clone(),
+            self.address.clone(),
+            self.port,
+        );
+        session.join_hub();
+        session
+    }
+
+    /// Starts receiving changes made through other sessions. Only sessions
+    /// someone reads from join; the HTTP transport's root server, which
+    /// just mints sessions, would otherwise queue every change.
+    fn join_hub(&self) {
+        self.hub.register(&self.notifier);
+    }
+
+    pub(crate) fn outbound(&self) -> Arc<AsyncMutex<Receiver<Value>>> {
+        self.outbound.clone()
     }
 
-    pub async fn run(&self) -> Result<()> {
+    /// Hands cards held by crashed agents back to the board in the background.
+    pub fn spawn_reaper(&self) {
+        tokio::spawn(reaper::run(self.redis.clone(), self.backend.clone(), self.hub.clone()));
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
         info!("Warp MCP server running on stdio");
         
-        let stdin = tokio::io::stdin();
-        let mut reader = BufReader::new(stdin);
-        let mut stdout = tokio::io::stdout();
+        self.join_hub();
+        self.spawn_reaper();
+        
+        // Responses and notifications share one writer so frames never interleave
+        let outbound = self.outbound.clone().try_lock_owned()
+            .map_err(|_| anyhow::anyhow!("MCP server is already running"))?;
+        let writer = tokio::spawn(write_stdout(outbound));
+        
+        let stdin = BufReader::new(tokio::io::stdin());
+        self.read_requests(stdin, get_max_concurrent_requests(), get_max_queued_requests()).await?;
+
+        // Let the writer drain whatever is still queued before we exit
+        self.notifier.send_reliably(Value::Null).await;
+        writer.await??;
+
+        Ok(())
+    }
+
+    /// Handles newline-delimited messages from `reader` until EOF, returning
+    /// once every request read has been answered. Requests run concurrently
+    /// and answer as they finish (matched by id). At most `max_concurrent`
+    /// run at once and up to `max_queued` more wait for a slot; requests
+    /// beyond that are answered with an error. Reading never waits for a
+    /// slot, so cancellations and the client's replies to our own requests
+    /// get through however busy the session is.
+    pub(crate) async fn read_requests<R: AsyncBufRead + Unpin>(
+        self: &Arc<Self>,
+        mut reader: R,
+        max_concurrent: usize,
+        max_queued: usize,
+    ) -> Result<()> {
+        let slots = Arc::new(Semaphore::new(max_concurrent));
+        // One place per line read and not yet answered, running or waiting
+        let places = Arc::new(Semaphore::new(max_concurrent + max_queued));
+        let mut running: Vec<JoinHandle<()>> = Vec::new();
         
         loop {
             let mut line = String::new();
-            line.clear();
             match reader.read_line(&mut line).await {
                 Ok(0) => break, // EOF
-                Ok(_) => {
-                    if let Some(response) = self.handle_request(&line).await {
-                        let response_str = serde_json::to_string(&response)?;
-                        stdout.write_all(response_str.as_bytes()).await?;
-                        stdout.write_all(b"\n").await?;
-                        stdout.flush().await?;
+                Ok(_) => match self.parse_request(&line) {
+                    Some(Ok(message)) => {
+                        // Replies to our own requests skip the queue: the
+                        // call waiting on one may be holding the last slot
+                        if self.notifier.resolve(&message) {
+                            continue;
+                        }
+                        // So do cancellations, or one could wait behind the
+                        // very request it cancels
+                        if let Some(params) = cancellation(&message) {
+                            self.cancel_request(params);
+                            continue;
+                        }
+                        let Ok(place) = places.clone().try_acquire_owned() else {
+                            if let Some(error) = self.busy_response(&message) {
+                                self.notifier.send_reliably(error).await;
+                            }
+                            continue;
+                        };
+                        running.retain(|task| !task.is_finished());
+                        running.push(self.dispatch(message, &slots, place));
                     }
-                }
+                    Some(Err(error)) => self.notifier.send_reliably(error).await,
+                    None => {}
+                },
                 Err(e) => {
-                    error!("Error reading from stdin: {}", e);
+                    error!("Error reading request: {}", e);
                     break;
                 }
             }
         }
 
+        // Wait until everything read has been answered
+        for task in running {
+            let _ = task.await;
+        }
         Ok(())
     }
 
-    async fn handle_request(&self, line: &str) -> Option<Value> {
+    /// Starts handling one message from the client in the background; the
+    /// response is queued for the writer when it is ready. Each request in
+    /// it waits there for a slot from `slots`, holding its `place` in the
+    /// queue until answered. Request ids are registered before the task is
+    /// spawned, so a cancellation read right after the request always finds
+    /// it.
+    pub(crate) fn dispatch(
+        self: &Arc<Self>,
+        payload: Value,
+        slots: &Arc<Semaphore>,
+        place: OwnedSemaphorePermit,
+    ) -> JoinHandle<()> {
+        let tracked = self.track_payload(payload);
+        let server = self.clone();
+        let slots = slots.clone();
+        tokio::spawn(async move {
+            let _place = place;
+            if let Some(response) = server.answer(tracked, Some(&slots)).await {
+                server.notifier.send_reliably(response).await;
+            }
+        })
+    }
+
+    /// The errors for the requests in `payload` when the queue is full;
+    /// `None` if it holds only notifications.
+    fn busy_response(&self, payload: &Value) -> Option<Value> {
+        let busy = |message: &Value| {
+            let id = message.get("id").filter(|_| message.get("method").is_some())?;
+            Some(self.error_response(Some(id.clone()), -32000, "Server busy: too many requests queued"))
+        };
+        match payload {
+            Value::Array(batch) => {
+                let errors: Vec<Value> = batch.iter().filter_map(busy).collect();
+                (!errors.is_empty()).then_some(Value::Array(errors))
+            }
+            message => busy(message),
+        }
+    }
+
+    /// Handles one message or a JSON-RPC batch array. A batch is answered
+    /// with one array holding the response of every request in it, in order;
+    /// notifications add nothing, so a batch of only notifications gets no
+    /// reply at all. With `slots`, each request waits for one of them.
+    pub(crate) async fn process_payload(self: &Arc<Self>, payload: Value, slots: Option<&Arc<Semaphore>>) -> Option<Value> {
+        let tracked = self.track_payload(payload);
+        self.answer(tracked, slots).await
+    }
+
+    /// Registers the request ids in `payload` for cancellation.
+    fn track_payload(&self, payload: Value) -> Tracked {
+        match payload {
+            Value::Array(batch) => Tracked::Batch(batch.into_iter().map(|m| self.track(m)).collect()),
+            message => Tracked::Single(self.track(message)),
+        }
+    }
+
+    /// Registers one message's request id. A request reusing the id of one
+    /// still in flight is refused, or the two would share one entry and
+    /// whichever finished first would drop the other's response.
+    fn track(&self, message: Value) -> TrackedMessage {
+        let key = match (message.get("method"), message.get("id")) {
+            (Some(_), Some(id)) => id.to_string(),
+            _ => return Ok((message, None)),
+        };
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.contains_key(&key) {
+            let id = message.get("id").cloned();
+            return Err(self.error_response(id, -32600, &format!("Request id {} is already in flight", key)));
+        }
+        in_flight.insert(key.clone(), None);
+        Ok((message, Some(key)))
+    }
+
+    /// Answers a tracked message. With `slots`, every request in it,
+    /// including each element of a batch, runs under the concurrency cap.
+    async fn answer(self: &Arc<Self>, tracked: Tracked, slots: Option<&Arc<Semaphore>>) -> Option<Value> {
+        let batch = match tracked {
+            Tracked::Batch(batch) => batch,
+            Tracked::Single(message) => return self.process_in_slot(message, slots.cloned()).await,
+        };
+        if batch.is_empty() {
+            return Some(self.error_response(None, -32600, "Invalid Request"));
+        }
+
+        // Elements run concurrently, just like requests on separate lines
+        let tasks: Vec<_> = batch
+            .into_iter()
+            .map(|message| {
+                let server = self.clone();
+                let slots = slots.cloned();
+                tokio::spawn(async move { server.process_in_slot(message, slots).await })
+            })
+            .collect();
+
+        let mut responses = Vec::new();
+        for task in tasks {
+            if let Ok(Some(response)) = task.await {
+                responses.push(response);
+            }
+        }
+        (!responses.is_empty()).then_some(Value::Array(responses))
+    }
+
+    /// Processes one message once it has a slot from `slots`, if given. Only
+    /// requests take a slot; replies to our own requests and cancellations
+    /// must get through while every slot is busy.
+    async fn process_in_slot(self: &Arc<Self>, message: TrackedMessage, slots: Option<Arc<Semaphore>>) -> Option<Value> {
+        let (message, key) = match message {
+            Ok(tracked) => tracked,
+            Err(refused) => return Some(refused),
+        };
+        let _permit = match (&key, slots) {
+            (Some(_), Some(slots)) => Some(slots.acquire_owned().await.ok()?),
+            _ => None,
+        };
+        self.process(message, key).await
+    }
+
+    /// Handles one parsed message as a task tracked under `key`, its
+    /// registered request id, so that `notifications/cancelled` can abort it.
+    /// Returns `None` for notifications, for the client's responses to our
+    /// own requests and for requests that were cancelled.
+    async fn process(self: &Arc<Self>, request: Value, key: Option<String>) -> Option<Value> {
+        if self.notifier.resolve(&request) {
+            return None;
+        }
+        if let Some(params) = cancellation(&request) {
+            self.cancel_request(params);
+            return None;
+        }
+
+        let id = request.get("id").cloned();
+        let abortable = self.is_abortable(&request);
+        let task = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            // Cancelled before it started: nothing to undo, so never start it
+            if key.as_ref().is_some_and(|key| !in_flight.contains_key(key)) {
+                return None;
+            }
+            let server = self.clone();
+            let task = tokio::spawn(async move { server.handle_message(request).await });
+            if let (Some(key), true) = (&key, abortable) {
+                in_flight.insert(key.clone(), Some(task.abort_handle()));
+            }
+            task
+        };
+        let result = task.await;
+        // A cancelled request is no longer tracked, and gets no response even
+        // if it had to run to the end
+        let cancelled = match &key {
+            Some(key) => self.in_flight.lock().unwrap().remove(key).is_none(),
+            None => false,
+        };
+
+        match result {
+            Ok(_) if cancelled => None,
+            Ok(response) => response,
+            Err(e) if e.is_cancelled() => None,
+            Err(e) => {
+                error!("Request handler panicked: {}", e);
+                Some(self.error_response(id, -32603, "Internal error"))
+            }
+        }
+    }
+
+    /// Cancels the request named by a `notifications/cancelled` message. Its
+    /// response is never sent; unknown or finished ids are ignored.
+    fn cancel_request(&self, params: &Value) {
+        let key = match params.get("requestId") {
+            Some(id) => id.to_string(),
+            None => return,
+        };
+
+        if let Some(task) = self.in_flight.lock().unwrap().remove(&key) {
+            let reason = params.get("reason").and_then(|r| r.as_str()).unwrap_or("no reason given");
+            match task {
+                Some(task) => {
+                    info!("Cancelling request {}: {}", key, reason);
+                    task.abort();
+                }
+                None => info!("Request {} cancelled ({}); it gets no response", key, reason),
+            }
+        }
+    }
+
+    /// Whether `request` can be aborted at any await point. Tool calls only
+    /// can if the tool says so: stopping between, say, claiming a card and
+    /// handing back its lease token would strand the claim.
+    fn is_abortable(&self, request: &Value) -> bool {
+        if request.get("method").and_then(|m| m.as_str()) != Some("tools/call") {
+            return true;
+        }
+        request
+            .pointer("/params/name")
+            .and_then(|n| n.as_str())
+            .is_some_and(|name| self.tools.is_abortable(name))
+    }
+
+    /// Handles one line and returns its response inline, bypassing the
+    /// writer.
+    #[cfg(test)]
+    pub(crate) async fn handle_request(self: &Arc<Self>, line: &str) -> Option<Value> {
+        match self.parse_request(line)? {
+            Ok(request) => self.process_payload(request, None).await,
+            Err(error) => Some(error),
+        }
+    }
+
+    /// Parses one line of input. Blank lines yield `None`; malformed JSON
+    /// yields the parse-error response to send back.
+    fn parse_request(&self, line: &str) -> Option<Result<Value, Value>> {
         let line = line.trim();
         if line.is_empty() {
             return None;
         }
 
-        let request: Value = match serde_json::from_str(line) {
-            Ok(req) => req,
+        match serde_json::from_str(line) {
+            Ok(request) => Some(Ok(request)),
             Err(e) => {
                 error!("Failed to parse JSON request: {}", e);
-                return Some(self.error_response(None, -32700, "Parse error"));
+                Some(Err(self.error_response(None, -32700, "Parse error")))
             }
-        };
+        }
+    }
 
+    async fn handle_message(&self, request: Value) -> Option<Value> {
         let id = request.get("id").cloned();
         let method = match request.get("method").and_then(|m| m.as_str()) {
             Some(method) => method,
@@ -81,22 +431,64 @@ impl MCPServer {
 
         let params = request.get("params").cloned().unwrap_or(Value::Null);
 
+        // Notifications carry no id and must never be answered, not even
+        // with an error
+        let Some(id) = id else {
+            self.handle_notification(method);
+            return None;
+        };
+        let id = Some(id);
+
         match method {
-            "initialize" => Some(self.handle_initialize(id)),
-            "tools/list" => Some(self.handle_tools_list(id)),
+            "initialize" => Some(self.handle_initialize(id, params)),
+            "ping" => Some(json!({"jsonrpc": "2.0", "id": id, "result": {}})),
+            "tools/list" => Some(self.handle_tools_list(id, params)),
             "tools/call" => Some(self.handle_tools_call(id, params).await),
+            "resources/list" => Some(self.handle_resources_list(id, params).await),
+            "resources/templates/list" => Some(self.handle_resource_templates_list(id, params)),
+            "resources/read" => Some(self.handle_resources_read(id, params).await),
+            "prompts/list" => Some(self.handle_prompts_list(id, params)),
+            "prompts/get" => Some(self.handle_prompts_get(id, params).await),
+            "resources/subscribe" => Some(self.handle_resources_subscribe(id, params, true)),
+            "resources/unsubscribe" => Some(self.handle_resources_subscribe(id, params, false)),
+            "logging/setLevel" => Some(self.handle_logging_set_level(id, params)),
+            "completion/complete" => Some(self.handle_completion_complete(id, params).await),
             _ => Some(self.error_response(id, -32601, "Method not found")),
         }
     }
 
-    fn handle_initialize(&self, id: Option<Value>) -> Value {
+    fn handle_notification(&self, method: &str) {
+        match method {
+            "notifications/initialized" => info!("Client finished initialization"),
+            // Cancellation is handled before dispatch, in `process`
+            _ => info!("Ignoring notification: {}", method),
+        }
+    }
+
+    fn handle_initialize(&self, id: Option<Value>, params: Value) -> Value {
+        // Echo the client's version if we speak it, otherwise offer our
+        // latest and let the client decide whether to carry on
+        self.notifier.set_client_capabilities(params.get("capabilities").cloned().unwrap_or_else(|| json!({})));
+
+        let requested = params.get("protocolVersion").and_then(|v| v.as_str());
+        let protocol_version = requested
+            .filter(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(v))
+            .unwrap_or(SUPPORTED_PROTOCOL_VERSIONS[0]);
+
         json!({
             "jsonrpc": "2.0",
             "id": id,
             "result": {
-                "protocolVersion": "2024-11-05",
+                "protocolVersion": protocol_version,
                 "capabilities": {
-                    "tools": {}
+                    "completions": {},
+                    "logging": {},
+                    "tools": {},
+                    "prompts": {},
+                    "resources": {
+                        "subscribe": true,
+                        "listChanged": true
+                    }
                 },
                 "serverInfo": {
                     "name": "warp-tasks-mcp",
@@ -106,156 +498,39 @@ impl MCPServer {
         })
     }
 
-    fn handle_tools_list(&self, id: Option<Value>) -> Value {
-        json!({
-            "jsonrpc": "2.0",
-            "id": id,
-            "result": {
-                "tools": [
-                    // Task Management Tools
-                    {
-                        "name": "scan_trello_tasks",
-                        "description": "List all Trello cards from configured boards - agents MUST use this to find tasks",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "list_filter": {
-                                    "type": "string",
-                                    "enum": ["todo", "in_progress", "done", "all"],
-                                    "description": "Filter cards by list"
-                                }
-                            },
-                            "required": []
-                        }
-                    },
-                    {
-                        "name": "take_trello_task",
-                        "description": "Claim a Trello task - REQUIRED before working on any task",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "agent_id": {
-                                    "type": "string",
-                                    "description": "Unique agent identifier"
-                                },
-                                "card_id": {
-                                    "type": "string", 
-                                    "description": "Trello card ID to claim"
-                                }
-                            },
-                            "required": ["agent_id", "card_id"]
-                        }
-                    },
-                    {
-                        "name": "update_trello_task",
-                        "description": "Update task progress, add comments, checklists - MUST be called frequently",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "agent_id": {"type": "string"},
-                                "card_id": {"type": "string"},
-                                "update_type": {
-                                    "type": "string",
-                                    "enum": ["comment", "checklist", "description", "move_list"]
-                                },
-                                "content": {"type": "string"},
-                                "list_id": {"type": "string", "description": "For move_list only"}
-                            },
-                            "required": ["agent_id", "card_id", "update_type", "content"]
-                        }
-                    },
-                    // Memory and Learning Tools
-                    {
-                        "name": "store_knowledge",
-                        "description": "Store task progress, learnings, API docs, or any knowledge with RAG tags",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "agent_id": {"type": "string"},
-                                "category": {
-                                    "type": "string",
-                                    "enum": ["task_progress", "api_docs", "code_patterns", "errors", "solutions", "project_knowledge"]
-                                },
-                                "key": {"type": "string"},
-                                "content": {"type": "string"},
-                                "tags": {
-                                    "type": "array",
-                                    "items": {"type": "string"},
-                                    "description": "RAG search tags"
-                                },
-                                "metadata": {"type": "object"}
-                            },
-                            "required": ["agent_id", "category", "key", "content", "tags"]
-                        }
-                    },
-                    {
-                        "name": "search_knowledge",
-                        "description": "RAG search across all stored knowledge using semantic queries",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "query": {"type": "string"},
-                                "category_filter": {"type": "string"},
-                                "agent_filter": {"type": "string"},
-                                "limit": {"type": "number", "default": 10}
-                            },
-                            "required": ["query"]
-                        }
-                    },
-                    {
-                        "name": "learn_from_agents",
-                        "description": "Query what other agents learned about specific topics or errors",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "topic": {"type": "string"},
-                                "error_pattern": {"type": "string"},
-                                "time_range": {"type": "string", "enum": ["hour", "day", "week", "all"]}
-                            },
-                            "required": ["topic"]
-                        }
-                    },
-                    // Heartbeat and Coordination
-                    {
-                        "name": "heartbeat",
-                        "description": "Send heartbeat with current task status - MUST be called every 30 seconds",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "agent_id": {"type": "string"},
-                                "card_id": {"type": "string"},
-                                "status": {"type": "string"},
-                                "progress_percentage": {"type": "number"}
-                            },
-                            "required": ["agent_id", "card_id", "status"]
-                        }
-                    },
-                    {
-                        "name": "check_agent_status",
-                        "description": "Check what other agents are working on to avoid collisions",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {},
-                            "required": []
-                        }
-                    },
-                    // Advanced Database Operations
-                    {
-                        "name": "execute_rag_query",
-                        "description": "Execute advanced RAG queries with RedisJSON for complex knowledge retrieval",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "json_path": {"type": "string"},
-                                "query": {"type": "string"},
-                                "aggregation": {"type": "string"}
-                            },
-                            "required": ["query"]
-                        }
-                    }
-                ]
+    fn handle_tools_list(&self, id: Option<Value>, params: Value) -> Value {
+        // Registry order, which groups related tools, rather than by name
+        self.list_page(id, &params, "tools", Value::from(self.tools.list()), None)
+    }
+
+    /// Answers a list method with the page of `items` that `params.cursor`
+    /// asks for, ordered by their `key` field (or as given without one),
+    /// under `field` and with `nextCursor` when more remain.
+    fn list_page(&self, id: Option<Value>, params: &Value, field: &str, items: Value, key: Option<&str>) -> Value {
+        let items = items.as_array().cloned().unwrap_or_default();
+        let cursor = params.get("cursor").and_then(|c| c.as_str());
+        let page = match key {
+            Some(key) => {
+                let sort_key = |item: &Value| item[key].as_str().unwrap_or_default().to_string();
+                pagination::paginate(items, sort_key, cursor, get_page_size())
             }
-        })
+            None => pagination::paginate_in_order(items, cursor, get_page_size()),
+        };
+
+        match page {
+            Ok((page, next_cursor)) => {
+                let mut result = json!({ field: page });
+                if let Some(next_cursor) = next_cursor {
+                    result["nextCursor"] = json!(next_cursor);
+                }
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": result
+                })
+            }
+            Err(e) => self.error_response(id, -32602, &e.to_string()),
+        }
     }
 
     async fn handle_tools_call(&self, id: Option<Value>, params: Value) -> Value {
@@ -267,45 +542,39 @@ impl MCPServer {
             }
         };
 
-        let result = match tool_call.name.as_str() {
-            "scan_trello_tasks" => {
-                tasks::scan_trello_tasks(&self.redis, &self.trello_client, tool_call.arguments).await
-            }
-            "take_trello_task" => {
-                tasks::take_trello_task(&self.redis, &self.trello_client, tool_call.arguments).await
-            }
-            "update_trello_task" => {
-                tasks::update_trello_task(&self.redis, &self.trello_client, tool_call.arguments).await
-            }
-            "store_knowledge" => {
-                memory::store_knowledge(&self.redis, tool_call.arguments).await
-            }
-            "search_knowledge" => {
-                memory::search_knowledge(&self.redis, tool_call.arguments).await
-            }
-            "learn_from_agents" => {
-                memory::learn_from_agents(&self.redis, tool_call.arguments).await
-            }
-            "heartbeat" => {
-                heartbeat::send_heartbeat(&self.redis, tool_call.arguments).await
-            }
-            "check_agent_status" => {
-                heartbeat::check_agent_status(&self.redis).await
-            }
-            "execute_rag_query" => {
-                database::execute_rag_query(&self.redis, tool_call.arguments).await
-            }
-            _ => {
-                return self.error_response(id, -32601, "Unknown tool");
-            }
+        let arguments = tool_call.arguments.clone();
+        let progress_token = tool_call.meta.and_then(|m| m.progress_token);
+        let progress = Progress::new(self.notifier.clone(), progress_token);
+        let ctx = ToolContext {
+            redis: &self.redis,
+            backend: self.backend.as_ref(),
+            progress: &progress,
+            client: &self.notifier,
         };
+        let result = self.tools.call(&tool_call.name, &ctx, tool_call.arguments).await;
+
+        // Bad requests are protocol errors; failures inside the tool are
+        // reported to the model as an error result
+        match &result {
+            Err(e) if e.is::<UnknownTool>() => return self.error_response(id, -32601, &e.to_string()),
+            Err(e) if e.is::<InvalidArguments>() => return self.error_response(id, -32602, &e.to_string()),
+            Err(e) if e.is::<InvalidCursor>() => return self.error_response(id, -32602, &e.to_string()),
+            _ => {}
+        }
+
+        if let Ok(output) = &result {
+            self.notify_tool_changes(&tool_call.name, &arguments, output);
+        }
 
         match result {
-            Ok(content) => json!({
+            // Older clients only read `content`, so mirror the structured
+            // result there as JSON text
+            Ok(output) => json!({
                 "jsonrpc": "2.0",
                 "id": id,
                 "result": {
-                    "content": [{"type": "text", "text": content}]
+                    "content": [{"type": "text", "text": output.to_string()}],
+                    "structuredContent": output
                 }
             }),
             Err(e) => json!({
@@ -319,6 +588,186 @@ impl MCPServer {
         }
     }
 
+    async fn handle_resources_list(&self, id: Option<Value>, params: Value) -> Value {
+        match resources::list_resources(&self.redis, self.backend.as_ref()).await {
+            Ok(list) => self.list_page(id, &params, "resources", Value::from(list), Some("uri")),
+            Err(e) => {
+                error!("Failed to list resources: {}", e);
+                self.error_response(id, -32603, &format!("Failed to list resources: {}", e))
+            }
+        }
+    }
+
+    fn handle_resource_templates_list(&self, id: Option<Value>, params: Value) -> Value {
+        self.list_page(id, &params, "resourceTemplates", resources::resource_templates(), Some("uriTemplate"))
+    }
+
+    async fn handle_resources_read(&self, id: Option<Value>, params: Value) -> Value {
+        let uri = match params.get("uri").and_then(|u| u.as_str()) {
+            Some(uri) => uri,
+            None => return self.error_response(id, -32602, "Invalid params: uri is required"),
+        };
+
+        match resources::read_resource(&self.redis, self.backend.as_ref(), uri).await {
+            Ok(contents) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "contents": contents
+                }
+            }),
+            Err(e) if e.is::<ResourceNotFound>() => self.error_response(id, -32002, &e.to_string()),
+            Err(e) => {
+                error!("Failed to read resource {}: {}", uri, e);
+                self.error_response(id, -32603, &format!("Failed to read resource: {}", e))
+            }
+        }
+    }
+
+    fn handle_resources_subscribe(&self, id: Option<Value>, params: Value, subscribe: bool) -> Value {
+        let uri = match params.get("uri").and_then(|u| u.as_str()) {
+            Some(uri) => uri,
+            None => return self.error_response(id, -32602, "Invalid params: uri is required"),
+        };
+
+        if subscribe {
+            self.notifier.subscribe(uri);
+        } else {
+            self.notifier.unsubscribe(uri);
+        }
+
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": {}
+        })
+    }
+
+    fn handle_logging_set_level(&self, id: Option<Value>, params: Value) -> Value {
+        let level = match params.get("level").and_then(|l| l.as_str()).and_then(logging::parse_mcp_level) {
+            Some(level) => level,
+            None => return self.error_response(id, -32602, "Invalid params: level must be an MCP log level"),
+        };
+
+        self.notifier.set_log_level(level);
+
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": {}
+        })
+    }
+
+    async fn handle_completion_complete(&self, id: Option<Value>, params: Value) -> Value {
+        let reference = params.get("ref").cloned().unwrap_or(Value::Null);
+        let (argument, value) = match (
+            params["argument"]["name"].as_str(),
+            params["argument"]["value"].as_str(),
+        ) {
+            (Some(argument), Some(value)) => (argument, value),
+            _ => return self.error_response(id, -32602, "Invalid params: argument name and value are required"),
+        };
+
+        let result = match self.check_completion_ref(&reference) {
+            Ok(()) => completions::complete(&self.redis, self.backend.as_ref(), argument, value).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(completion) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": {
+                    "completion": completion
+                }
+            }),
+            Err(e) if e.is::<InvalidCompletionRequest>() => self.error_response(id, -32602, &e.to_string()),
+            Err(e) => {
+                error!("Failed to complete {}: {}", argument, e);
+                self.error_response(id, -32603, &format!("Failed to complete argument: {}", e))
+            }
+        }
+    }
+
+    /// Checks that a completion `ref` names a prompt, resource template or
+    /// tool we serve. `ref/tool` is not in the MCP spec but lets clients
+    /// complete tool arguments too.
+    fn check_completion_ref(&self, reference: &Value) -> Result<()> {
+        let named = |items: Value, field: &str, name: &Value| {
+            items.as_array().is_some_and(|items| items.iter().any(|item| &item[field] == name))
+        };
+
+        let known = match reference.get("type").and_then(|t| t.as_str()) {
+            Some("ref/prompt") => named(prompts::list_prompts(), "name", &reference["name"]),
+            Some("ref/resource") => named(resources::resource_templates(), "uriTemplate", &reference["uri"]),
+            Some("ref/tool") => named(Value::Array(self.tools.list()), "name", &reference["name"]),
+            _ => false,
+        };
+
+        if known {
+            Ok(())
+        } else {
+            Err(InvalidCompletionRequest(format!("Unknown completion reference: {}", reference)).into())
+        }
+    }
+
+    fn handle_prompts_list(&self, id: Option<Value>, params: Value) -> Value {
+        self.list_page(id, &params, "prompts", prompts::list_prompts(), Some("name"))
+    }
+
+    async fn handle_prompts_get(&self, id: Option<Value>, params: Value) -> Value {
+        let name = match params.get("name").and_then(|n| n.as_str()) {
+            Some(name) => name,
+            None => return self.error_response(id, -32602, "Invalid params: name is required"),
+        };
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+        match prompts::get_prompt(&self.redis, self.backend.as_ref(), name, &arguments).await {
+            Ok(prompt) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": prompt
+            }),
+            Err(e) if e.is::<InvalidPromptRequest>() => self.error_response(id, -32602, &e.to_string()),
+            Err(e) => {
+                error!("Failed to render prompt {}: {}", name, e);
+                self.error_response(id, -32603, &format!("Failed to render prompt: {}", e))
+            }
+        }
+    }
+
+    /// Tells subscribers on every session about resources a successful tool
+    /// call, which returned `output`, just changed.
+    fn notify_tool_changes(&self, tool: &str, arguments: &Value, output: &Value) {
+        let card_id = arguments.get("card_id").and_then(|v| v.as_str());
+
+        match tool {
+            "take_trello_task" | "update_trello_task" | "release_trello_task" | "complete_trello_task" => {
+                if let Some(card_id) = card_id {
+                    self.hub.resource_updated(&resources::card_uri(card_id));
+                }
+            }
+            // An upsert that replaced an entry changed it in place
+            "store_knowledge" => match output.get("id").and_then(|v| v.as_str()) {
+                Some(id) if output.get("updated").and_then(|v| v.as_bool()) == Some(true) => {
+                    self.hub.resource_updated(&resources::knowledge_uri(id))
+                }
+                _ => self.hub.resource_list_changed(),
+            },
+            "update_knowledge" | "revert_knowledge" | "delete_knowledge" => {
+                if let Some(id) = arguments.get("id").and_then(|v| v.as_str()) {
+                    self.hub.resource_updated(&resources::knowledge_uri(id));
+                }
+                if tool == "delete_knowledge" {
+                    self.hub.resource_list_changed();
+                }
+            }
+            // Every heartbeat moves the agent's last_seen/progress forward
+            "heartbeat" => self.hub.resource_updated(resources::ACTIVE_AGENTS_URI),
+            _ => {}
+        }
+    }
+
     pub fn address(&self) -> &str {
         &self.address
     }
@@ -337,4 +786,40 @@ impl MCPServer {
             }
         })
     }
-}
\ No newline at end of file
+}
+
+/// A message or batch whose request ids are registered for cancellation.
+enum Tracked {
+    Single(TrackedMessage),
+    Batch(Vec<TrackedMessage>),
+}
+
+/// A message paired with its key in `in_flight`, or the error response for
+/// a request whose id was already in flight.
+type TrackedMessage = Result<(Value, Option<String>), Value>;
+
+/// The params of a `notifications/cancelled` message.
+fn cancellation(message: &Value) -> Option<&Value> {
+    if message.get("method").and_then(|m| m.as_str()) != Some("notifications/cancelled") {
+        return None;
+    }
+    Some(message.get("params").unwrap_or(&Value::Null))
+}
+
+/// Writes queued messages to stdout as newline-delimited JSON until it sees
+/// the `Value::Null` shutdown marker.
+async fn write_stdout(mut outbound: OwnedMutexGuard<Receiver<Value>>) -> Result<()> {
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(message) = outbound.recv().await {
+        if message.is_null() {
+            break;
+        }
+        let message_str = serde_json::to_string(&message)?;
+        stdout.write_all(message_str.as_bytes()).await?;
+        stdout.write_all(b"\n").await?;
+        stdout.flush().await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::memory::MemoryBackend;
+    use crate::http;
+    use crate::server::MCPServer;
+    use crate::tests::support::{server_with_tools, unconnected_redis, Gate};
+    use crate::tools::registry::ToolRegistry;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    // The handshake and session handling never reach Redis
+    async fn serve() -> String {
+        serve_with_token(None).await
+    }
+
+    async fn serve_with_token(token: Option<&str>) -> String {
+        let server = MCPServer::new(unconnected_redis(), Arc::new(MemoryBackend::new())).await.unwrap();
+        serve_server(Arc::new(server), token.map(str::to_string), 16).await
+    }
+
+    async fn serve_server(server: Arc<MCPServer>, token: Option<String>, max_concurrent: usize) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, http::router(server, token, max_concurrent)).await });
+        url
+    }
+
+    /// Opens a session on `url`, returning its id.
+    async fn open_session(client: &reqwest::Client, url: &str) -> String {
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {}}
+        });
+        let response = client.post(url).json(&initialize).send().await.unwrap();
+        response.headers()["mcp-session-id"].to_str().unwrap().to_string()
+    }
+
+    fn ping() -> Value {
+        json!({"jsonrpc": "2.0", "id": 2, "method": "ping"})
+    }
+
+    #[tokio::test]
+    async fn test_initialize_opens_a_session() {
+        let url = serve().await;
+        let client = reqwest::Client::new();
+
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {}}
+        });
+        let response = client.post(&url).json(&initialize).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let session_id = response.headers()["mcp-session-id"].to_str().unwrap().to_string();
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["result"]["protocolVersion"], "2025-06-18");
+
+        let pong = client.post(&url).header("mcp-session-id", &session_id).json(&ping()).send().await.unwrap();
+        assert_eq!(pong.status(), 200);
+        assert_eq!(pong.json::<Value>().await.unwrap()["result"], json!({}));
+
+        let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        let accepted = client.post(&url).header("mcp-session-id", &session_id).json(&initialized).send().await.unwrap();
+        assert_eq!(accepted.status(), 202);
+
+        // Closing the session makes its id unknown
+        let closed = client.delete(&url).header("mcp-session-id", &session_id).send().await.unwrap();
+        assert_eq!(closed.status(), 204);
+        let gone = client.post(&url).header("mcp-session-id", &session_id).json(&ping()).send().await.unwrap();
+        assert_eq!(gone.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_or_missing_session_is_rejected() {
+        let url = serve().await;
+        let client = reqwest::Client::new();
+
+        let unknown = client.post(&url).header("mcp-session-id", "no-such-session").json(&ping()).send().await.unwrap();
+        assert_eq!(unknown.status(), 404);
+
+        let missing = client.post(&url).json(&ping()).send().await.unwrap();
+        assert_eq!(missing.status(), 400);
+
+        let stream = client.get(&url).header("mcp-session-id", "no-such-session").send().await.unwrap();
+        assert_eq!(stream.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_origins_are_rejected() {
+        let url = serve().await;
+        let client = reqwest::Client::new();
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {}}
+        });
+
+        let foreign = client.post(&url).header("origin", "http://evil.example").json(&initialize).send().await.unwrap();
+        assert_eq!(foreign.status(), 403);
+
+        let local = client.post(&url).header("origin", "http://localhost:3000").json(&initialize).send().await.unwrap();
+        assert_eq!(local.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_the_token_are_rejected() {
+        let url = serve_with_token(Some("s3cret")).await;
+        let client = reqwest::Client::new();
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {}}
+        });
+
+        let missing = client.post(&url).json(&initialize).send().await.unwrap();
+        assert_eq!(missing.status(), 401);
+
+        let wrong = client.post(&url).bearer_auth("guess").json(&initialize).send().await.unwrap();
+        assert_eq!(wrong.status(), 401);
+
+        let right = client.post(&url).bearer_auth("s3cret").json(&initialize).send().await.unwrap();
+        assert_eq!(right.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_requests_across_sessions_share_the_cap() {
+        let gate = Gate::new();
+        let mut tools = ToolRegistry::new();
+        tools.register(gate.clone());
+        let url = serve_server(server_with_tools(tools), None, 2).await;
+        let client = reqwest::Client::new();
+
+        let calls: Vec<_> = (1..=3)
+            .map(|id| {
+                let (client, url) = (client.clone(), url.clone());
+                tokio::spawn(async move {
+                    let session_id = open_session(&client, &url).await;
+                    let call = json!({
+                        "jsonrpc": "2.0", "id": id, "method": "tools/call",
+                        "params": {"name": "gate", "arguments": {}}
+                    });
+                    let response = client.post(&url).header("mcp-session-id", &session_id).json(&call).send().await.unwrap();
+                    response.json::<Value>().await.unwrap()
+                })
+            })
+            .collect();
+
+        // The third call is not started while two hold the slots
+        gate.wait_until_entered(2).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(gate.entered(), 2);
+
+        gate.open(3);
+        for call in calls {
+            assert!(call.await.unwrap()["result"].is_object());
+        }
+        assert_eq!(gate.peak(), 2);
+    }
+}
@@ -0,0 +1,5 @@
+mod http_test;
+mod notifications_test;
+mod reaper_test;
+mod server_test;
+pub(crate) mod support;
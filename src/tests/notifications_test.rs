@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use crate::notifications::Notifier;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_request_fails_fast_when_it_cannot_be_queued() {
+        let (outbound, mut rx) = mpsc::channel(1);
+        let notifier = Notifier::new(outbound);
+        notifier.notify("notifications/progress", json!({}));
+
+        // The queue is full: the request errors instead of waiting out the timeout
+        let started = std::time::Instant::now();
+        let full = notifier.request("sampling/createMessage", json!({})).await;
+        assert!(full.unwrap_err().to_string().contains("queue is full"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        rx.close();
+        let closed = notifier.request("elicitation/create", json!({})).await;
+        assert!(closed.unwrap_err().to_string().contains("session has closed"));
+    }
+}
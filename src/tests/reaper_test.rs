@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::memory::MemoryBackend;
+    use crate::backends::TaskBackend;
+    use crate::notifications::NotifierHub;
+    use crate::reaper;
+    use crate::schemas::TaskAssignment;
+    use crate::tests::support::test_redis;
+    use crate::tools::assignment;
+    use redis::aio::Connection;
+    use redis::AsyncCommands;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// A claim made an hour ago with no heartbeat since, whose lease has
+    /// `ttl` seconds left.
+    async fn aged_claim(conn: &mut Connection, card_id: &str, agent_id: &str, ttl: u64) {
+        let claim = TaskAssignment {
+            agent_id: agent_id.to_string(),
+            lease_token: Uuid::new_v4().to_string(),
+            claimed_at: chrono::Utc::now().timestamp() - 3600,
+        };
+        let _: () = conn
+            .set_ex(assignment::assignment_key(card_id), serde_json::to_string(&claim).unwrap(), ttl)
+            .await
+            .unwrap();
+        let _: () = conn.hset(assignment::ASSIGNMENTS_INDEX, card_id, agent_id).await.unwrap();
+        let _: () = conn.sadd(format!("agent:{}:tasks", agent_id), card_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_reaper_reclaims_only_lapsed_leases() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let backend = MemoryBackend::new();
+
+        let (lapsed, quiet) = (Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        backend.add_card(&lapsed, "Lapsed lease", "in_progress");
+        backend.add_card(&quiet, "Quiet but leased", "in_progress");
+        aged_claim(&mut conn, &lapsed, "agent-a", 1).await;
+        aged_claim(&mut conn, &quiet, "agent-b", 600).await;
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+
+        reaper::reap_stale_assignments(&redis, &backend, &NotifierHub::new()).await.unwrap();
+
+        // The lapsed claim is gone and its card is back on the board
+        assert_eq!(backend.get_card(&lapsed).await.unwrap().list_id, "todo");
+        assert!(backend.comments.lock().unwrap()[&lapsed][0].starts_with("Task reclaimed from agent agent-a"));
+        let indexed: Option<String> = conn.hget(assignment::ASSIGNMENTS_INDEX, &lapsed).await.unwrap();
+        assert!(indexed.is_none());
+        let held: bool = conn.sismember("agent:agent-a:tasks", &lapsed).await.unwrap();
+        assert!(!held);
+
+        // A missed heartbeat alone is not enough while the lease lasts
+        assert_eq!(backend.get_card(&quiet).await.unwrap().list_id, "in_progress");
+        assert_eq!(assignment::get(&mut conn, &quiet).await.unwrap().unwrap().agent_id, "agent-b");
+        let _: () = conn.del(assignment::assignment_key(&quiet)).await.unwrap();
+        let _: () = conn.hdel(assignment::ASSIGNMENTS_INDEX, &quiet).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_claims_are_indexed_for_the_reaper() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let card_id = Uuid::new_v4().to_string();
+
+        assert!(assignment::claim(&mut conn, &card_id, "agent-a").await.unwrap().is_some());
+        let indexed: Option<String> = conn.hget(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+        assert_eq!(indexed.as_deref(), Some("agent-a"));
+
+        // A losing claim leaves both the lease and the index to the winner
+        assert!(assignment::claim(&mut conn, &card_id, "agent-b").await.unwrap().is_none());
+        let indexed: Option<String> = conn.hget(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+        assert_eq!(indexed.as_deref(), Some("agent-a"));
+
+        let _: () = conn.del(assignment::assignment_key(&card_id)).await.unwrap();
+        let _: () = conn.hdel(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+    }
+}
@@ -0,0 +1,485 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::TaskBackend;
+    use crate::server::MCPServer;
+    use crate::tests::support::{
+        call_tool, server_with_board, server_with_tools, test_redis, tool_request, unconnected_redis, Gate,
+    };
+    use crate::tools::assignment;
+    use crate::tools::registry::ToolRegistry;
+    use redis::AsyncCommands;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::io::{AsyncWriteExt, BufReader};
+    use tokio::sync::Semaphore;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_batch_request() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, _) = server_with_board(test_redis().await, &card_id).await;
+
+        let batch = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "tools/list"},
+            {"jsonrpc": "2.0", "id": 2, "method": "resources/read", "params": {"uri": format!("trello://card/{}", card_id)}},
+            {"jsonrpc": "2.0", "id": 3}
+        ]);
+        let responses = server.handle_request(&batch.to_string()).await.unwrap();
+        let responses = responses.as_array().unwrap();
+
+        assert_eq!(responses.len(), 3);
+        // Tools come back in registry order, not sorted by name
+        let names = |tools: &[Value]| tools.iter().map(|t| t["name"].clone()).collect::<Vec<_>>();
+        assert_eq!(
+            names(responses[0]["result"]["tools"].as_array().unwrap()),
+            names(&ToolRegistry::builtin().list())
+        );
+        assert!(responses[1]["result"]["contents"].is_array());
+        assert_eq!(responses[2]["error"]["code"], -32600);
+
+        let empty = server.handle_request("[]").await.unwrap();
+        assert_eq!(empty["error"]["code"], -32600);
+    }
+
+    #[tokio::test]
+    async fn test_batch_elements_count_against_the_cap() {
+        let (server, gate) = gated_server();
+        let batch: Vec<Value> = (1..=6)
+            .map(|id| json!({"jsonrpc": "2.0", "id": id, "method": "tools/call", "params": {"name": "gate", "arguments": {}}}))
+            .collect();
+        let input = format!("{}\n", Value::Array(batch));
+        let reading = tokio::spawn({
+            let server = server.clone();
+            async move { server.read_requests(input.as_bytes(), 2, 4).await }
+        });
+
+        gate.wait_until_entered(2).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(gate.entered(), 2);
+
+        gate.open(6);
+        let outbound = server.outbound();
+        let responses = outbound.lock().await.recv().await.unwrap();
+        assert_eq!(responses.as_array().unwrap().len(), 6);
+        reading.await.unwrap().unwrap();
+        assert_eq!(gate.peak(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_notifications() {
+        let (server, _) = server_with_board(unconnected_redis(), &Uuid::new_v4().to_string()).await;
+
+        let initialize = |version: &str| json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": version, "capabilities": {}, "clientInfo": {"name": "test", "version": "0"}}
+        }).to_string();
+        let old = server.handle_request(&initialize("2024-11-05")).await.unwrap();
+        assert_eq!(old["result"]["protocolVersion"], "2024-11-05");
+        let unknown = server.handle_request(&initialize("1999-01-01")).await.unwrap();
+        assert_eq!(unknown["result"]["protocolVersion"], "2025-06-18");
+
+        let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle_request(&initialized.to_string()).await.is_none());
+        let unknown = json!({"jsonrpc": "2.0", "method": "notifications/whatever"});
+        assert!(server.handle_request(&unknown.to_string()).await.is_none());
+
+        let pong = server.handle_request(r#"{"jsonrpc": "2.0", "id": 7, "method": "ping"}"#).await.unwrap();
+        assert_eq!(pong["id"], 7);
+        assert_eq!(pong["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_logging_set_level() {
+        let (server, _) = server_with_board(unconnected_redis(), &Uuid::new_v4().to_string()).await;
+
+        let set_level = |level: &str| json!({
+            "jsonrpc": "2.0", "id": 1, "method": "logging/setLevel", "params": {"level": level}
+        }).to_string();
+        let ok = server.handle_request(&set_level("warning")).await.unwrap();
+        assert_eq!(ok["result"], json!({}));
+        let bad = server.handle_request(&set_level("verbose")).await.unwrap();
+        assert_eq!(bad["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_completion_for_card_ids_and_categories() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, _) = server_with_board(test_redis().await, &card_id).await;
+
+        let complete = |reference: Value, argument: &str, value: &str| json!({
+            "jsonrpc": "2.0", "id": 1, "method": "completion/complete",
+            "params": {"ref": reference, "argument": {"name": argument, "value": value}}
+        }).to_string();
+
+        // Cards match on their title as well as their id
+        let prompt = json!({"type": "ref/prompt", "name": "write_handoff_note"});
+        let cards = server.handle_request(&complete(prompt, "card_id", "write")).await.unwrap();
+        assert!(cards["result"]["completion"]["values"].as_array().unwrap().contains(&json!(card_id)));
+
+        let tool = json!({"type": "ref/tool", "name": "store_knowledge"});
+        let categories = server.handle_request(&complete(tool, "category", "er")).await.unwrap();
+        let categories = categories["result"]["completion"]["values"].as_array().unwrap().clone();
+        assert!(categories.contains(&json!("errors")));
+        assert!(!categories.contains(&json!("solutions")));
+
+        let unknown = json!({"type": "ref/prompt", "name": "no_such_prompt"});
+        let error = server.handle_request(&complete(unknown, "card_id", "")).await.unwrap();
+        assert_eq!(error["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_move_list_without_list_id_elicits_the_list() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, backend) = server_with_board(test_redis().await, &card_id).await;
+
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}
+        });
+        server.handle_request(&initialize.to_string()).await.unwrap();
+
+        let claim = call_tool(&server, "take_trello_task", json!({"agent_id": "agent-a", "card_id": card_id})).await;
+        let lease_token = claim["structuredContent"]["lease_token"].as_str().unwrap().to_string();
+
+        let update = tokio::spawn({
+            let server = server.clone();
+            let card_id = card_id.clone();
+            async move {
+                call_tool(&server, "update_trello_task", json!({
+                    "agent_id": "agent-a", "card_id": card_id, "update_type": "move_list",
+                    "content": "starting work", "lease_token": lease_token
+                })).await
+            }
+        });
+
+        // The server asks which list to use before moving anything
+        let outbound = server.outbound();
+        let request = outbound.lock().await.recv().await.unwrap();
+        assert_eq!(request["method"], "elicitation/create");
+        let choices = request["params"]["requestedSchema"]["properties"]["list_id"]["enum"].clone();
+        assert!(choices.as_array().unwrap().contains(&json!("in_progress")));
+
+        let answer = json!({
+            "jsonrpc": "2.0", "id": request["id"],
+            "result": {"action": "accept", "content": {"list_id": "in_progress"}}
+        });
+        assert!(server.handle_request(&answer.to_string()).await.is_none());
+
+        let update = update.await.unwrap();
+        assert!(update.get("isError").is_none());
+        assert_eq!(backend.get_card(&card_id).await.unwrap().list_id, "in_progress");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_scan_pages_through_cards_with_a_cursor() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, backend) = server_with_board(test_redis().await, &card_id).await;
+        // Board order, which is not card id order
+        let board = [card_id.clone(), format!("{}-b", card_id), format!("{}-a", card_id)];
+        backend.add_card(&board[1], "Review the docs", "todo");
+        backend.add_card(&board[2], "Publish the docs", "todo");
+
+        let scan = |cursor: Value| call_tool(&server, "scan_trello_tasks", json!({"list_filter": "todo", "limit": 2, "cursor": cursor}));
+        let ids = |page: &Value| page["cards"].as_array().unwrap().iter().map(|c| c["id"].clone()).collect::<Vec<_>>();
+        let first = scan(Value::Null).await["structuredContent"].clone();
+        assert_eq!(first["total"], 3);
+        assert_eq!(ids(&first), vec![json!(board[0]), json!(board[1])]);
+
+        // Taking a card off the list before the cursor shifts nothing
+        backend.move_card(&board[0], "in_progress").await.unwrap();
+        let second = scan(first["next_cursor"].clone()).await["structuredContent"].clone();
+        assert_eq!(ids(&second), vec![json!(board[2])]);
+        assert!(second["next_cursor"].is_null());
+
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": {"cursor": "bogus"}});
+        let error = server.handle_request(&request.to_string()).await.unwrap();
+        assert_eq!(error["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_scan_reports_progress_against_the_callers_token() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, backend) = server_with_board(test_redis().await, &card_id).await;
+        backend.add_card(&format!("{}-2", card_id), "Review the docs", "todo");
+        backend.add_card(&format!("{}-3", card_id), "Publish the docs", "todo");
+
+        let request = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+            "params": {"name": "scan_trello_tasks", "arguments": {}, "_meta": {"progressToken": "scan-1"}}
+        });
+        let response = server.handle_request(&request.to_string()).await.unwrap();
+        assert!(response["result"].get("isError").is_none());
+
+        let outbound = server.outbound();
+        let mut outbound = outbound.lock().await;
+        let mut progress = Vec::new();
+        while let Ok(message) = outbound.try_recv() {
+            assert_eq!(message["method"], "notifications/progress");
+            assert_eq!(message["params"]["progressToken"], "scan-1");
+            progress.push(message["params"].clone());
+        }
+        assert!(progress.len() > 2);
+        let counts: Vec<u64> = progress.iter().map(|p| p["progress"].as_u64().unwrap()).collect();
+        assert!(counts.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", counts);
+        let last = progress.last().unwrap();
+        assert_eq!(last["progress"], last["total"]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_upsert_notifies_subscribers_of_the_entry() {
+        let (server, _) = server_with_board(test_redis().await, &Uuid::new_v4().to_string()).await;
+        let session = Arc::new(server.new_session());
+
+        let key = Uuid::new_v4().to_string();
+        let store = |content: &str| json!({
+            "agent_id": "agent-a", "category": "api_docs", "key": key,
+            "content": content, "tags": ["docs"], "upsert": true
+        });
+        let stored = call_tool(&session, "store_knowledge", store("v1")).await;
+        let uri = format!("knowledge://{}", stored["structuredContent"]["id"].as_str().unwrap());
+        let subscribe = json!({"jsonrpc": "2.0", "id": 1, "method": "resources/subscribe", "params": {"uri": uri}});
+        session.handle_request(&subscribe.to_string()).await.unwrap();
+
+        // A new entry changes the list; replacing it changes the entry
+        call_tool(&session, "store_knowledge", store("v2")).await;
+        let outbound = session.outbound();
+        let mut outbound = outbound.lock().await;
+        assert_eq!(outbound.recv().await.unwrap()["method"], "notifications/resources/list_changed");
+        let updated = outbound.recv().await.unwrap();
+        assert_eq!(updated["method"], "notifications/resources/updated");
+        assert_eq!(updated["params"]["uri"], json!(uri));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_cancelled_request_gets_no_response() {
+        let card_id = Uuid::new_v4().to_string();
+        let (server, backend) = server_with_board(test_redis().await, &card_id).await;
+
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}
+        });
+        server.handle_request(&initialize.to_string()).await.unwrap();
+
+        let claim = call_tool(&server, "take_trello_task", json!({"agent_id": "agent-a", "card_id": card_id})).await;
+        let lease_token = claim["structuredContent"]["lease_token"].as_str().unwrap().to_string();
+
+        // Waits on the elicitation below until we answer it
+        let slow = json!({
+            "jsonrpc": "2.0", "id": "slow", "method": "tools/call",
+            "params": {"name": "update_trello_task", "arguments": {
+                "agent_id": "agent-a", "card_id": card_id, "update_type": "move_list",
+                "content": "starting work", "lease_token": lease_token
+            }}
+        });
+        let update = tokio::spawn({
+            let server = server.clone();
+            async move { server.handle_request(&slow.to_string()).await }
+        });
+
+        let outbound = server.outbound();
+        let request = outbound.lock().await.recv().await.unwrap();
+        assert_eq!(request["method"], "elicitation/create");
+
+        let cancel = json!({
+            "jsonrpc": "2.0", "method": "notifications/cancelled",
+            "params": {"requestId": "slow", "reason": "user gave up"}
+        });
+        assert!(server.handle_request(&cancel.to_string()).await.is_none());
+
+        let answer = json!({
+            "jsonrpc": "2.0", "id": request["id"],
+            "result": {"action": "accept", "content": {"list_id": "in_progress"}}
+        });
+        server.handle_request(&answer.to_string()).await;
+
+        // The update changes the board, so it runs to the end, but its
+        // response is dropped
+        assert!(update.await.unwrap().is_none());
+        assert_eq!(backend.get_card(&card_id).await.unwrap().list_id, "in_progress");
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_read_before_the_request_starts() {
+        let (server, _) = server_with_board(unconnected_redis(), &Uuid::new_v4().to_string()).await;
+        let slots = Arc::new(Semaphore::new(2));
+        let places = Arc::new(Semaphore::new(2));
+        let ping = |id: u64| json!({"jsonrpc": "2.0", "id": id, "method": "ping"});
+
+        // The cancellation is handled before the dispatched task gets to run
+        server.dispatch(ping(5), &slots, places.clone().try_acquire_owned().unwrap());
+        let cancel = json!({"jsonrpc": "2.0", "method": "notifications/cancelled", "params": {"requestId": 5}});
+        assert!(server.handle_request(&cancel.to_string()).await.is_none());
+        server.dispatch(ping(6), &slots, places.clone().try_acquire_owned().unwrap());
+
+        let outbound = server.outbound();
+        assert_eq!(outbound.lock().await.recv().await.unwrap()["id"], 6);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_next_task_prompt_looks_past_the_first_page() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let card = |n: usize| format!("next-task-prompt-{:02}", n);
+
+        // A full first page of claimed cards, then one free card
+        let (server, backend) = server_with_board(redis, &card(0)).await;
+        for n in 1..=50 {
+            backend.add_card(&card(n), "Busy", "todo");
+        }
+        for n in 0..50 {
+            let _: () = conn.del(assignment::assignment_key(&card(n))).await.unwrap();
+            assignment::claim(&mut conn, &card(n), "agent-b").await.unwrap().unwrap();
+        }
+        let _: () = conn.del(assignment::assignment_key(&card(50))).await.unwrap();
+
+        let request = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "prompts/get",
+            "params": {"name": "pick_up_next_task", "arguments": {"agent_id": "agent-a"}}
+        });
+        let prompt = server.handle_request(&request.to_string()).await.unwrap();
+        let text = prompt["result"]["messages"][0]["content"]["text"].as_str().unwrap().to_string();
+        assert!(text.contains(&card(50)));
+        assert!(!text.contains(&card(49)));
+
+        for n in 0..50 {
+            let _: () = conn.del(assignment::assignment_key(&card(n))).await.unwrap();
+            let _: () = conn.hdel(assignment::ASSIGNMENTS_INDEX, card(n)).await.unwrap();
+        }
+    }
+
+    /// A server offering only a [`Gate`] tool, which the test drives.
+    fn gated_server() -> (Arc<MCPServer>, Gate) {
+        let gate = Gate::new();
+        let mut tools = ToolRegistry::new();
+        tools.register(gate.clone());
+        (server_with_tools(tools), gate)
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_does_not_hold_up_a_later_one() {
+        let (server, gate) = gated_server();
+        let input = tool_request(json!("slow"), "gate", json!({})) + &tool_request(json!("fast"), "gate", json!({"pass": true}));
+        let reading = tokio::spawn({
+            let server = server.clone();
+            async move { server.read_requests(input.as_bytes(), 4, 4).await }
+        });
+
+        let outbound = server.outbound();
+        let mut outbound = outbound.lock().await;
+        assert_eq!(outbound.recv().await.unwrap()["id"], "fast");
+        gate.open(1);
+        assert_eq!(outbound.recv().await.unwrap()["id"], "slow");
+        reading.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_past_the_cap_waits_for_a_slot() {
+        let (server, gate) = gated_server();
+        let input: String = (1..=3).map(|id| tool_request(json!(id), "gate", json!({}))).collect();
+        let reading = tokio::spawn({
+            let server = server.clone();
+            async move { server.read_requests(input.as_bytes(), 2, 4).await }
+        });
+
+        // The third request is not started while two hold the slots
+        gate.wait_until_entered(2).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(gate.entered(), 2);
+
+        gate.open(1);
+        gate.wait_until_entered(3).await;
+        gate.open(2);
+        let outbound = server.outbound();
+        let mut outbound = outbound.lock().await;
+        for _ in 0..3 {
+            assert!(outbound.recv().await.unwrap()["result"].is_object());
+        }
+        reading.await.unwrap().unwrap();
+        assert_eq!(gate.peak(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replies_are_read_while_every_slot_is_busy() {
+        let (server, gate) = gated_server();
+        let (mut client, input) = tokio::io::duplex(4096);
+        let reading = tokio::spawn({
+            let server = server.clone();
+            async move { server.read_requests(BufReader::new(input), 1, 4).await }
+        });
+
+        // The only slot goes to a call waiting on our reply, and another
+        // request queues up behind it
+        client.write_all(tool_request(json!("ask"), "gate", json!({"ask": true, "pass": true})).as_bytes()).await.unwrap();
+        gate.wait_until_entered(1).await;
+        client.write_all(tool_request(json!("next"), "gate", json!({"pass": true})).as_bytes()).await.unwrap();
+
+        let outbound = server.outbound();
+        let mut outbound = outbound.lock().await;
+        let request = outbound.recv().await.unwrap();
+        assert_eq!(request["method"], "elicitation/create");
+        let answer = json!({"jsonrpc": "2.0", "id": request["id"], "result": {"action": "accept", "content": {}}});
+        client.write_all(format!("{}\n", answer).as_bytes()).await.unwrap();
+        drop(client);
+
+        let answered = tokio::time::timeout(Duration::from_secs(5), outbound.recv()).await;
+        assert_eq!(answered.expect("the reply was never read").unwrap()["id"], "ask");
+        assert_eq!(outbound.recv().await.unwrap()["id"], "next");
+        reading.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_past_a_full_queue_is_turned_away() {
+        let (server, gate) = gated_server();
+        let input: String = (1..=3).map(|id| tool_request(json!(id), "gate", json!({}))).collect();
+        let reading = tokio::spawn({
+            let server = server.clone();
+            async move { server.read_requests(input.as_bytes(), 1, 1).await }
+        });
+
+        // One request runs and one waits; the third gets an error at once
+        let outbound = server.outbound();
+        let mut outbound = outbound.lock().await;
+        let busy = outbound.recv().await.unwrap();
+        assert_eq!(busy["id"], 3);
+        assert_eq!(busy["error"]["code"], -32000);
+
+        gate.open(2);
+        for _ in 0..2 {
+            assert!(outbound.recv().await.unwrap()["result"].is_object());
+        }
+        reading.await.unwrap().unwrap();
+        assert_eq!(gate.entered(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_request_reusing_an_id_in_flight_is_refused() {
+        let (server, gate) = gated_server();
+        let input = tool_request(json!(1), "gate", json!({})) + &tool_request(json!(1), "gate", json!({"pass": true}));
+        let reading = tokio::spawn({
+            let server = server.clone();
+            async move { server.read_requests(input.as_bytes(), 4, 4).await }
+        });
+
+        let outbound = server.outbound();
+        let mut outbound = outbound.lock().await;
+        let refused = outbound.recv().await.unwrap();
+        assert_eq!(refused["id"], 1);
+        assert_eq!(refused["error"]["code"], -32600);
+
+        // The first request still gets its response
+        gate.open(1);
+        assert!(outbound.recv().await.unwrap()["result"].is_object());
+        reading.await.unwrap().unwrap();
+        assert_eq!(gate.entered(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::backends::memory::MemoryBackend;
+use crate::server::MCPServer;
+use crate::tools::registry::{Tool, ToolContext, ToolRegistry};
+use crate::utils::RedisManager;
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// The Redis Stack server named by `TEST_REDIS_URL`, for tests that need a
+/// real one. Those tests are `#[ignore]`d so a plain `cargo test` never
+/// touches a developer's Redis; run them with `cargo test -- --ignored`
+/// against a throwaway server.
+pub async fn test_redis() -> RedisManager {
+    let redis_url = std::env::var("TEST_REDIS_URL")
+        .expect("TEST_REDIS_URL must point at a throwaway Redis Stack server");
+    RedisManager::new(&redis_url).await.unwrap()
+}
+
+/// A manager for tests whose code paths never reach Redis; using it fails
+/// on connect.
+pub fn unconnected_redis() -> RedisManager {
+    RedisManager::unconnected("redis://127.0.0.1:6379").unwrap()
+}
+
+/// A server over a `MemoryBackend` whose todo list holds just `card_id`.
+pub async fn server_with_board(redis: RedisManager, card_id: &str) -> (Arc<MCPServer>, Arc<MemoryBackend>) {
+    let backend = Arc::new(MemoryBackend::new());
+    backend.add_card(card_id, "Write the docs", "todo");
+
+    let server = MCPServer::new(redis, backend.clone()).await.unwrap();
+    (Arc::new(server), backend)
+}
+
+/// Calls tool `name` through `tools/call` and returns the call's `result`.
+pub async fn call_tool(server: &Arc<MCPServer>, name: &str, arguments: Value) -> Value {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": {"name": name, "arguments": arguments}
+    });
+    server.handle_request(&request.to_string()).await.unwrap()["result"].clone()
+}
+
+/// A server over an empty `MemoryBackend` offering only `tools`, for tests
+/// that never reach Redis.
+pub fn server_with_tools(tools: ToolRegistry) -> Arc<MCPServer> {
+    Arc::new(MCPServer::with_tools(unconnected_redis(), Arc::new(MemoryBackend::new()), tools))
+}
+
+/// A `tools/call` request for tool `name` as one line of input.
+pub fn tool_request(id: Value, name: &str, arguments: Value) -> String {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": {"name": name, "arguments": arguments}
+    });
+    format!("{}\n", request)
+}
+
+/// A tool whose calls wait until the test opens the gate for them, counting
+/// how many have started and how many ran at once.
+#[derive(Clone)]
+pub struct Gate(Arc<GateState>);
+
+pub struct GateState {
+    open: Semaphore,
+    entered: AtomicUsize,
+    running: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GateArgs {
+    /// Return at once instead of waiting at the gate
+    #[serde(default)]
+    pub pass: bool,
+    /// Ask the client to go ahead first, and wait for its reply
+    #[serde(default)]
+    pub ask: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GateResult {}
+
+impl Gate {
+    pub fn new() -> Self {
+        Self(Arc::new(GateState {
+            open: Semaphore::new(0),
+            entered: AtomicUsize::new(0),
+            running: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Lets `calls` more waiting calls through.
+    pub fn open(&self, calls: usize) {
+        self.0.open.add_permits(calls);
+    }
+
+    pub fn entered(&self) -> usize {
+        self.0.entered.load(Ordering::SeqCst)
+    }
+
+    pub fn peak(&self) -> usize {
+        self.0.peak.load(Ordering::SeqCst)
+    }
+
+    /// Waits for `calls` calls to have started, failing after five seconds.
+    pub async fn wait_until_entered(&self, calls: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while self.entered() < calls {
+            assert!(Instant::now() < deadline, "only {} of {} calls started", self.entered(), calls);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for Gate {
+    const NAME: &'static str = "gate";
+    const DESCRIPTION: &'static str = "Waits until the test lets it through";
+
+    type Args = GateArgs;
+    type Output = GateResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: GateArgs) -> anyhow::Result<GateResult> {
+        let state = &self.0;
+        state.entered.fetch_add(1, Ordering::SeqCst);
+        let running = state.running.fetch_add(1, Ordering::SeqCst) + 1;
+        state.peak.fetch_max(running, Ordering::SeqCst);
+        if args.ask {
+            let params = json!({"message": "Go ahead?", "requestedSchema": {"type": "object", "properties": {}}});
+            ctx.client.request("elicitation/create", params).await?;
+        }
+        if !args.pass {
+            state.open.acquire().await?.forget();
+        }
+        state.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(GateResult {})
+    }
+}
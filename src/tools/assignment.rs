@@ -0,0 +1,179 @@
+use crate::schemas::TaskAssignment;
+use crate::utils::get_lease_timeout;
+use anyhow::Result;
+use redis::aio::Connection;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+/// Hash of card id -> owning agent id for every live claim. Lease keys expire
+/// on their own, so this is what lets the reaper find claims that lapsed.
+pub const ASSIGNMENTS_INDEX: &str = "assignments";
+
+pub fn assignment_key(card_id: &str) -> String {
+    format!("assignment:{}", card_id)
+}
+
+/// Atomically claims `card_id` for `agent_id`.
+///
+/// The assignment is written with `SET NX`, so exactly one agent can win a
+/// race on the same card, and recorded in [`ASSIGNMENTS_INDEX`] in the same
+/// script so the reaper can never miss it. The lease lasts `LEASE_TIMEOUT`
+/// seconds and is extended by [`renew`] on every heartbeat from the owner.
+/// Returns the new assignment (including its lease token) on success, or
+/// `None` if the card is already held by someone.
+pub async fn claim(
+    conn: &mut Connection,
+    card_id: &str,
+    agent_id: &str,
+) -> Result<Option<TaskAssignment>> {
+    let assignment = TaskAssignment {
+        agent_id: agent_id.to_string(),
+        lease_token: Uuid::new_v4().to_string(),
+        claimed_at: chrono::Utc::now().timestamp(),
+    };
+
+    let claimed: i32 = redis::Script::new(include_str!("../scripts/claim_assignment.lua"))
+        .key(assignment_key(card_id))
+        .key(ASSIGNMENTS_INDEX)
+        .arg(serde_json::to_string(&assignment)?)
+        .arg(get_lease_timeout())
+        .arg(card_id)
+        .arg(agent_id)
+        .invoke_async(conn)
+        .await?;
+
+    if claimed != 1 {
+        return Ok(None);
+    }
+
+    Ok(Some(assignment))
+}
+
+pub async fn get(conn: &mut Connection, card_id: &str) -> Result<Option<TaskAssignment>> {
+    let raw: Option<String> = conn.get(assignment_key(card_id)).await?;
+    match raw {
+        Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+        None => Ok(None),
+    }
+}
+
+/// Makes sure `agent_id` currently holds the lease on `card_id` under
+/// `lease_token`, returning the assignment if so.
+pub async fn verify_lease(
+    conn: &mut Connection,
+    card_id: &str,
+    agent_id: &str,
+    lease_token: &str,
+) -> Result<TaskAssignment> {
+    let assignment = get(conn, card_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Task {} is not assigned to any agent", card_id))?;
+
+    if assignment.agent_id != agent_id || assignment.lease_token != lease_token {
+        return Err(anyhow::anyhow!(
+            "Agent {} does not hold the lease on task {}",
+            agent_id,
+            card_id
+        ));
+    }
+
+    Ok(assignment)
+}
+
+/// Extends the lease on `card_id` if `agent_id` still owns it.
+///
+/// Returns `false` when the card is unassigned or held by another agent, in
+/// which case nothing is changed.
+pub async fn renew(conn: &mut Connection, card_id: &str, agent_id: &str) -> Result<bool> {
+    let renewed: i32 = redis::Script::new(include_str!("../scripts/renew_assignment.lua"))
+        .key(assignment_key(card_id))
+        .arg(agent_id)
+        .arg(get_lease_timeout())
+        .invoke_async(conn)
+        .await?;
+
+    Ok(renewed == 1)
+}
+
+/// Gives up the lease on `card_id` and drops the card from the owner's task
+/// set. Fails unless `agent_id` holds the lease under `lease_token`.
+pub async fn release(
+    conn: &mut Connection,
+    card_id: &str,
+    agent_id: &str,
+    lease_token: &str,
+) -> Result<()> {
+    let released: i32 = redis::Script::new(include_str!("../scripts/release_assignment.lua"))
+        .key(assignment_key(card_id))
+        .key(ASSIGNMENTS_INDEX)
+        .arg(agent_id)
+        .arg(lease_token)
+        .arg(card_id)
+        .invoke_async(conn)
+        .await?;
+
+    if released != 1 {
+        return Err(anyhow::anyhow!(
+            "Agent {} does not hold the lease on task {}",
+            agent_id,
+            card_id
+        ));
+    }
+
+    let agent_tasks_key = format!("agent:{}:tasks", agent_id);
+    let _: () = conn.srem(&agent_tasks_key, card_id).await?;
+
+    Ok(())
+}
+
+/// Forgets `agent_id`'s claim on `card_id` once its lease has lapsed, which
+/// happens `LEASE_TIMEOUT` seconds after the last heartbeat. Returns `true`
+/// if the claim was reclaimed.
+pub async fn reap(conn: &mut Connection, card_id: &str, agent_id: &str) -> Result<bool> {
+    let reaped: i32 = redis::Script::new(include_str!("../scripts/reap_assignment.lua"))
+        .key(assignment_key(card_id))
+        .key(ASSIGNMENTS_INDEX)
+        .arg(card_id)
+        .arg(agent_id)
+        .invoke_async(conn)
+        .await?;
+
+    if reaped != 1 {
+        return Ok(false);
+    }
+
+    let agent_tasks_key = format!("agent:{}:tasks", agent_id);
+    let _: () = conn.srem(&agent_tasks_key, card_id).await?;
+
+    Ok(true)
+}
+
+/// Takes `card_id` away from `agent_id` while its lease is still live, for a
+/// takeover the caller agreed to. Only succeeds if there has been no
+/// heartbeat for the card since `grace_cutoff` and the claim is older than
+/// that. Returns `true` if the claim was dropped.
+pub async fn revoke(
+    conn: &mut Connection,
+    card_id: &str,
+    agent_id: &str,
+    grace_cutoff: i64,
+) -> Result<bool> {
+    let revoked: i32 = redis::Script::new(include_str!("../scripts/revoke_assignment.lua"))
+        .key(assignment_key(card_id))
+        .key(ASSIGNMENTS_INDEX)
+        .key(format!("heartbeat:{}:{}", agent_id, card_id))
+        .arg(card_id)
+        .arg(agent_id)
+        .arg(grace_cutoff)
+        .invoke_async(conn)
+        .await?;
+
+    if revoked != 1 {
+        return Ok(false);
+    }
+
+    let agent_tasks_key = format!("agent:{}:tasks", agent_id);
+    let _: () = conn.srem(&agent_tasks_key, card_id).await?;
+
+    Ok(true)
+}
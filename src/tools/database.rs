@@ -1,46 +1,65 @@
+use crate::notifications::Progress;
+use crate::schemas::{RagMatch, RagQueryArgs, RagQueryResult};
+use crate::tools::registry::{Tool, ToolContext};
 use crate::utils::RedisManager;
 use anyhow::Result;
+use async_trait::async_trait;
 use redis::{AsyncCommands, JsonAsyncCommands};
-use serde_json::{json, Value};
+
+pub struct RagQuery;
+
+#[async_trait]
+impl Tool for RagQuery {
+    const NAME: &'static str = "execute_rag_query";
+    const DESCRIPTION: &'static str =
+        "Execute advanced RAG queries with RedisJSON for complex knowledge retrieval";
+    const READ_ONLY: bool = true;
+
+    type Args = RagQueryArgs;
+    type Output = RagQueryResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: RagQueryArgs) -> Result<RagQueryResult> {
+        execute_rag_query(ctx.redis, args, ctx.progress).await
+    }
+}
 
 pub async fn execute_rag_query(
     redis: &RedisManager,
-    args: Value,
-) -> Result<String> {
+    params: RagQueryArgs,
+    progress: &Progress,
+) -> Result<RagQueryResult> {
     let mut conn = redis.get_connection().await?;
     
-    let query = args.get("query")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Query required"))?;
+    let query = params.query.as_str();
     
     // Use RedisJSON path queries for complex RAG operations
-    let json_path = args.get("json_path")
-        .and_then(|v| v.as_str())
-        .unwrap_or("$");
+    let json_path = params.json_path.as_deref().unwrap_or("$");
     
     // Example: Find all knowledge entries matching criteria
     let pattern = "knowledge:*";
     let keys: Vec<String> = conn.keys(pattern).await?;
     
     let mut results = Vec::new();
+    let total = keys.len();
     
-    for key in keys {
+    for (done, key) in keys.into_iter().enumerate() {
         // Use JSON path queries
         let matches: Option<String> = conn.json_get(&key, json_path).await?;
         if let Some(json_str) = matches {
             if json_str.contains(query) {
-                results.push(json!({
-                    "key": key,
-                    "match": json_str
-                }));
+                results.push(RagMatch {
+                    key: key.clone(),
+                    matched: json_str,
+                });
             }
         }
+        progress.report(done + 1, Some(total), &format!("Scanned {}", key));
     }
     
-    Ok(json!({
-        "query": query,
-        "path": json_path,
-        "results": results,
-        "count": results.len()
-    }).to_string())
+    Ok(RagQueryResult {
+        query: query.to_string(),
+        path: json_path.to_string(),
+        count: results.len(),
+        results,
+    })
 }
\ No newline at end of file
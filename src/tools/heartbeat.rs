@@ -1,14 +1,31 @@
 use crate::schemas::*;
-use crate::utils::{RedisManager, get_heartbeat_timeout};
+use crate::tools::assignment;
+use crate::tools::registry::{Tool, ToolContext};
+use crate::utils::{RedisManager, get_heartbeat_timeout, get_lease_timeout};
 use anyhow::Result;
+use async_trait::async_trait;
 use redis::AsyncCommands;
-use serde_json::{json, Value};
+
+pub struct Heartbeat;
+
+#[async_trait]
+impl Tool for Heartbeat {
+    const NAME: &'static str = "heartbeat";
+    const DESCRIPTION: &'static str =
+        "Report current task status; the lease on the claimed card is extended. Send heartbeats more often than the lease timeout";
+
+    type Args = HeartbeatArgs;
+    type Output = HeartbeatResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: HeartbeatArgs) -> Result<HeartbeatResult> {
+        send_heartbeat(ctx.redis, args).await
+    }
+}
 
 pub async fn send_heartbeat(
     redis: &RedisManager,
-    args: Value,
-) -> Result<String> {
-    let params: HeartbeatArgs = serde_json::from_value(args)?;
+    params: HeartbeatArgs,
+) -> Result<HeartbeatResult> {
     let mut conn = redis.get_connection().await?;
     
     let heartbeat_key = format!("heartbeat:{}:{}", params.agent_id, params.card_id);
@@ -28,13 +45,46 @@ pub async fn send_heartbeat(
     let _: () = conn.set_ex(&heartbeat_key, serde_json::to_string(&status)?, timeout).await?;
     
     // Update agent's active status
-    let active_key = format!("active_agents");
-    let _: () = conn.zadd(&active_key, &params.agent_id, timestamp as f64).await?;
+    let active_key = "active_agents";
+    let _: () = conn.zadd(active_key, &params.agent_id, timestamp as f64).await?;
     
-    Ok(format!("Heartbeat recorded for agent {} on task {}", params.agent_id, params.card_id))
+    // Keep the agent's claim on the card alive for another lease window
+    let renewed = assignment::renew(&mut conn, &params.card_id, &params.agent_id).await?;
+    let lease = if renewed {
+        format!("lease renewed for {}s", get_lease_timeout())
+    } else {
+        "no lease held on this task".to_string()
+    };
+    
+    Ok(HeartbeatResult {
+        message: format!(
+            "Heartbeat recorded for agent {} on task {} ({})",
+            params.agent_id, params.card_id, lease
+        ),
+        agent_id: params.agent_id,
+        card_id: params.card_id,
+        lease_renewed: renewed,
+    })
+}
+
+pub struct CheckAgentStatus;
+
+#[async_trait]
+impl Tool for CheckAgentStatus {
+    const NAME: &'static str = "check_agent_status";
+    const DESCRIPTION: &'static str =
+        "Check what other agents are working on to avoid collisions";
+    const READ_ONLY: bool = true;
+
+    type Args = CheckAgentStatusArgs;
+    type Output = AgentStatusResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, _args: CheckAgentStatusArgs) -> Result<AgentStatusResult> {
+        check_agent_status(ctx.redis).await
+    }
 }
 
-pub async fn check_agent_status(redis: &RedisManager) -> Result<String> {
+pub async fn check_agent_status(redis: &RedisManager) -> Result<AgentStatusResult> {
     let mut conn = redis.get_connection().await?;
     let pattern = "heartbeat:*";
     let keys: Vec<String> = conn.keys(pattern).await?;
@@ -45,13 +95,13 @@ pub async fn check_agent_status(redis: &RedisManager) -> Result<String> {
         let status_str: Option<String> = conn.get(&key).await?;
         if let Some(status_json) = status_str {
             if let Ok(status) = serde_json::from_str::<AgentStatus>(&status_json) {
-                active_agents.push(json!({
-                    "agent_id": status.agent_id,
-                    "card_id": status.card_id,
-                    "status": status.status,
-                    "progress": status.progress,
-                    "last_seen": status.last_heartbeat
-                }));
+                active_agents.push(ActiveAgent {
+                    agent_id: status.agent_id,
+                    card_id: status.card_id,
+                    status: status.status,
+                    progress: status.progress,
+                    last_seen: status.last_heartbeat,
+                });
             }
         }
     }
@@ -59,10 +109,10 @@ pub async fn check_agent_status(redis: &RedisManager) -> Result<String> {
     // Clean up stale agents
     let active_key = "active_agents";
     let cutoff = chrono::Utc::now().timestamp() - get_heartbeat_timeout() as i64;
-    let _: () = conn.zrembyscore(&active_key, "-inf", cutoff as f64).await?;
+    let _: () = conn.zrembyscore(active_key, "-inf", cutoff as f64).await?;
     
-    Ok(json!({
-        "active_agents": active_agents,
-        "total_active": active_agents.len()
-    }).to_string())
+    Ok(AgentStatusResult {
+        total_active: active_agents.len(),
+        active_agents,
+    })
 }
\ No newline at end of file
@@ -1,127 +1,639 @@
+use crate::notifications::Notifier;
 use crate::schemas::*;
-use crate::utils::RedisManager;
+use crate::tools::registry::{Tool, ToolContext};
+use crate::utils::{RedisManager, get_knowledge_admins};
 use anyhow::Result;
+use async_trait::async_trait;
+use log::warn;
+use redis::aio::Connection;
 use redis::{AsyncCommands, JsonAsyncCommands};
 use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
 use uuid::Uuid;
 
-use super::search::{SearchIndex, SearchParams};
+use super::search::SearchIndex;
 
 const MEMORY_EXPIRATION: i64 = 604800; // 7 days in seconds
+/// Hash of `category:key` to the id of the entry last stored under it
+const KNOWLEDGE_KEYS: &str = "knowledge_keys";
+/// How often a write that lost a race to another writer re-reads and retries
+const SAVE_ATTEMPTS: usize = 5;
 
 async fn ensure_index(redis: &RedisManager) -> Result<()> {
     let index = SearchIndex::new("knowledge-idx");
     index.create(redis).await
 }
 
+/// Reads one knowledge entry, or `None` if it does not exist (or expired).
+pub async fn get_knowledge(redis: &RedisManager, id: &str) -> Result<Option<KnowledgeEntry>> {
+    let mut conn = redis.get_connection().await?;
+    let raw: Option<String> = conn.json_get(format!("knowledge:{}", id), "$").await?;
+    Ok(raw.and_then(parse_entry))
+}
+
+/// Every stored knowledge entry, fetched in one round trip.
+pub async fn all_knowledge(redis: &RedisManager) -> Result<Vec<KnowledgeEntry>> {
+    let mut conn = redis.get_connection().await?;
+    let keys: Vec<String> = conn.keys("knowledge:*").await?;
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let raw: Vec<Option<String>> = redis::cmd("JSON.MGET")
+        .arg(&keys)
+        .arg("$")
+        .query_async(&mut conn)
+        .await?;
+    Ok(raw.into_iter().flatten().filter_map(parse_entry).collect())
+}
+
+/// An entry as `JSON.GET`/`JSON.MGET` return it for the `$` path, which
+/// wraps it in an array.
+fn parse_entry(json: String) -> Option<KnowledgeEntry> {
+    serde_json::from_str::<Vec<KnowledgeEntry>>(&json)
+        .ok()
+        .and_then(|mut entries| entries.pop())
+}
+
+pub struct StoreKnowledge;
+
+#[async_trait]
+impl Tool for StoreKnowledge {
+    const NAME: &'static str = "store_knowledge";
+    const DESCRIPTION: &'static str =
+        "Store task progress, learnings, API docs, or any knowledge with RAG tags";
+
+    type Args = StoreKnowledgeArgs;
+    type Output = StoreKnowledgeResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: StoreKnowledgeArgs) -> Result<StoreKnowledgeResult> {
+        store_knowledge(ctx.redis, args).await
+    }
+}
+
 pub async fn store_knowledge(
     redis: &RedisManager,
-    args: Value,
-) -> Result<String> {
-    let params: StoreKnowledgeArgs = serde_json::from_value(args)?;
+    params: StoreKnowledgeArgs,
+) -> Result<StoreKnowledgeResult> {
     let mut conn = redis.get_connection().await?;
     
-    let knowledge_id = Uuid::new_v4().to_string();
-    let timestamp = chrono::Utc::now().timestamp();
-    
-    let entry = KnowledgeEntry {
-        id: knowledge_id.clone(),
-        agent_id: params.agent_id.clone(),
-        category: params.category.clone(),
-        key: params.key.clone(),
-        content: params.content,
-        tags: params.tags.clone(),
-        metadata: params.metadata.unwrap_or(json!({})),
-        created_at: timestamp,
-        updated_at: timestamp,
-        access_count: 0,
-    };
-    
     // Ensure search index exists
     if let Err(e) = ensure_index(redis).await {
-        eprintln!("Warning: Failed to create search index: {}", e);
+        warn!("Failed to create search index: {}", e);
     }
 
-    // Store in RedisJSON for complex queries
-    let json_key = format!("knowledge:{}", knowledge_id);
-    let _: () = conn.json_set(&json_key, "$", &entry).await?;
-    let _: () = conn.expire(&json_key, MEMORY_EXPIRATION).await?;
-    
-    Ok(format!("Knowledge stored with ID: {}", knowledge_id))
+    let field = key_field(&params.category, &params.key);
+    for _ in 0..SAVE_ATTEMPTS {
+        // An upsert replaces whatever the (category, key) index points at.
+        // A key seen for the first time is reserved before it is written, so
+        // concurrent upserts of it all end up on the same entry
+        let id = if params.upsert.unwrap_or(false) {
+            match conn.hget::<_, _, Option<String>>(KNOWLEDGE_KEYS, &field).await? {
+                Some(id) => id,
+                None => {
+                    let id = Uuid::new_v4().to_string();
+                    let reserved: bool = conn.hset_nx(KNOWLEDGE_KEYS, &field, &id).await?;
+                    if !reserved {
+                        continue;
+                    }
+                    id
+                }
+            }
+        } else {
+            Uuid::new_v4().to_string()
+        };
+        let existing = get_knowledge(redis, &id).await?;
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let entry = match &existing {
+            Some(existing) => KnowledgeEntry {
+                content: params.content.clone(),
+                tags: params.tags.clone(),
+                metadata: params.metadata.clone().unwrap_or_else(|| existing.metadata.clone()),
+                updated_at: timestamp,
+                version: existing.version + 1,
+                ..existing.clone()
+            },
+            None => KnowledgeEntry {
+                id,
+                agent_id: params.agent_id.clone(),
+                category: params.category.clone(),
+                key: params.key.clone(),
+                content: params.content.clone(),
+                tags: params.tags.clone(),
+                metadata: params.metadata.clone().unwrap_or(json!({})),
+                created_at: timestamp,
+                updated_at: timestamp,
+                access_count: 0,
+                version: 1,
+            },
+        };
+
+        if !save_version(&mut conn, existing.as_ref(), &entry, &params.agent_id, None).await? {
+            continue;
+        }
+
+        let updated = existing.is_some();
+        let message = if updated {
+            format!("Knowledge updated with ID: {}", entry.id)
+        } else {
+            format!("Knowledge stored with ID: {}", entry.id)
+        };
+        return Ok(StoreKnowledgeResult {
+            message,
+            id: entry.id,
+            updated,
+        });
+    }
+
+    Err(anyhow::anyhow!("Knowledge {} kept changing while it was stored; try again", field))
+}
+
+/// Field of the `(category, key)` → id index hash for an entry.
+fn key_field(category: &str, key: &str) -> String {
+    format!("{}:{}", category, key)
+}
+
+/// Writes `entry` as the version following `previous` (`None` for a new
+/// entry): saves it, restarts its expiry, points its `(category, key)` at it
+/// and records it in the history as written by `author`. Returns `false`,
+/// having written nothing, if another writer changed the entry since
+/// `previous` was read.
+async fn save_version(
+    conn: &mut Connection,
+    previous: Option<&KnowledgeEntry>,
+    entry: &KnowledgeEntry,
+    author: &str,
+    reverted_from: Option<u32>,
+) -> Result<bool> {
+    // Entries stored before versioning have no history yet, so their
+    // previous state is recorded first
+    let first = match previous {
+        Some(previous) => serde_json::to_string(&version_of(previous, &previous.agent_id, KnowledgeDiff::default(), None))?,
+        None => String::new(),
+    };
+    let diff = previous.map(|p| diff(p, entry)).unwrap_or_default();
+    let version = version_of(entry, author, diff, reverted_from);
+
+    let saved: i32 = redis::Script::new(include_str!("../scripts/save_knowledge.lua"))
+        .key(format!("knowledge:{}", entry.id))
+        .key(KNOWLEDGE_KEYS)
+        .key(history_key(&entry.id))
+        .arg(previous.map_or(0, |p| p.version))
+        .arg(serde_json::to_string(entry)?)
+        .arg(key_field(&entry.category, &entry.key))
+        .arg(&entry.id)
+        .arg(MEMORY_EXPIRATION)
+        .arg(first)
+        .arg(serde_json::to_string(&version)?)
+        .invoke_async(conn)
+        .await?;
+
+    Ok(saved == 1)
+}
+
+fn history_key(id: &str) -> String {
+    format!("knowledge_history:{}", id)
+}
+
+fn version_of(
+    entry: &KnowledgeEntry,
+    author: &str,
+    diff: KnowledgeDiff,
+    reverted_from: Option<u32>,
+) -> KnowledgeVersion {
+    KnowledgeVersion {
+        version: entry.version,
+        agent_id: author.to_string(),
+        timestamp: entry.updated_at,
+        content: entry.content.clone(),
+        tags: entry.tags.clone(),
+        metadata: entry.metadata.clone(),
+        diff,
+        reverted_from,
+    }
+}
+
+/// Line-level content changes plus tag and metadata-key changes.
+fn diff(before: &KnowledgeEntry, after: &KnowledgeEntry) -> KnowledgeDiff {
+    fn missing_from(items: &[String], other: &[String]) -> Vec<String> {
+        items.iter().filter(|item| !other.contains(item)).cloned().collect()
+    }
+    let lines = |content: &str| content.lines().map(str::to_string).collect::<Vec<_>>();
+    let (old_lines, new_lines) = (lines(&before.content), lines(&after.content));
+
+    let metadata_changed = match (before.metadata.as_object(), after.metadata.as_object()) {
+        (Some(old), Some(new)) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            keys.into_iter()
+                .filter(|key| old.get(*key) != new.get(*key))
+                .cloned()
+                .collect()
+        }
+        // Not key/value metadata, so the whole value is the change
+        _ if before.metadata != after.metadata => vec!["$".to_string()],
+        _ => Vec::new(),
+    };
+
+    KnowledgeDiff {
+        content_added: missing_from(&new_lines, &old_lines),
+        content_removed: missing_from(&old_lines, &new_lines),
+        tags_added: missing_from(&after.tags, &before.tags),
+        tags_removed: missing_from(&before.tags, &after.tags),
+        metadata_changed,
+    }
+}
+
+/// Every recorded version of `entry`, oldest first.
+async fn load_history(conn: &mut Connection, entry: &KnowledgeEntry) -> Result<Vec<KnowledgeVersion>> {
+    let raw: Vec<String> = conn.lrange(history_key(&entry.id), 0, -1).await?;
+    let versions: Vec<KnowledgeVersion> = raw
+        .iter()
+        .filter_map(|json| serde_json::from_str(json).ok())
+        .collect();
+
+    // Never changed since versioning began: the entry is its only version
+    if versions.is_empty() {
+        return Ok(vec![version_of(entry, &entry.agent_id, KnowledgeDiff::default(), None)]);
+    }
+    Ok(versions)
+}
+
+pub struct UpdateKnowledge;
+
+#[async_trait]
+impl Tool for UpdateKnowledge {
+    const NAME: &'static str = "update_knowledge";
+    const DESCRIPTION: &'static str =
+        "Correct the content, tags or metadata of a stored knowledge entry";
+
+    type Args = UpdateKnowledgeArgs;
+    type Output = KnowledgeActionResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: UpdateKnowledgeArgs) -> Result<KnowledgeActionResult> {
+        update_knowledge(ctx.redis, args).await
+    }
+}
+
+pub async fn update_knowledge(
+    redis: &RedisManager,
+    params: UpdateKnowledgeArgs,
+) -> Result<KnowledgeActionResult> {
+    let mut conn = redis.get_connection().await?;
+
+    // Re-read and retry if another agent changed the entry meanwhile, so
+    // no two changes claim the same version
+    for _ in 0..SAVE_ATTEMPTS {
+        let previous = get_knowledge(redis, &params.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Knowledge entry {} not found", params.id))?;
+        let mut entry = previous.clone();
+
+        if let Some(content) = &params.content {
+            entry.content = content.clone();
+        }
+        if let Some(tags) = &params.tags {
+            entry.tags = tags.clone();
+        }
+        if let Some(metadata) = &params.metadata {
+            entry.metadata = metadata.clone();
+        }
+        entry.updated_at = chrono::Utc::now().timestamp();
+        entry.version += 1;
+
+        if save_version(&mut conn, Some(&previous), &entry, &params.agent_id, None).await? {
+            return Ok(KnowledgeActionResult {
+                message: format!("Knowledge entry {} updated to version {} by {}", entry.id, entry.version, params.agent_id),
+                id: entry.id,
+            });
+        }
+    }
+
+    Err(anyhow::anyhow!("Knowledge entry {} kept changing while it was updated; try again", params.id))
+}
+
+pub struct GetKnowledgeHistory;
+
+#[async_trait]
+impl Tool for GetKnowledgeHistory {
+    const NAME: &'static str = "get_knowledge_history";
+    const DESCRIPTION: &'static str =
+        "List every version of a knowledge entry with who changed what and when";
+    const READ_ONLY: bool = true;
+
+    type Args = KnowledgeHistoryArgs;
+    type Output = KnowledgeHistoryResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: KnowledgeHistoryArgs) -> Result<KnowledgeHistoryResult> {
+        get_knowledge_history(ctx.redis, args).await
+    }
+}
+
+pub async fn get_knowledge_history(
+    redis: &RedisManager,
+    params: KnowledgeHistoryArgs,
+) -> Result<KnowledgeHistoryResult> {
+    let entry = get_knowledge(redis, &params.id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Knowledge entry {} not found", params.id))?;
+
+    let mut conn = redis.get_connection().await?;
+    let versions = load_history(&mut conn, &entry).await?;
+
+    Ok(KnowledgeHistoryResult {
+        id: entry.id,
+        current_version: entry.version,
+        versions,
+    })
+}
+
+pub struct RevertKnowledge;
+
+#[async_trait]
+impl Tool for RevertKnowledge {
+    const NAME: &'static str = "revert_knowledge";
+    const DESCRIPTION: &'static str =
+        "Restore the content, tags and metadata of an earlier version of a knowledge entry";
+
+    type Args = RevertKnowledgeArgs;
+    type Output = KnowledgeActionResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: RevertKnowledgeArgs) -> Result<KnowledgeActionResult> {
+        revert_knowledge(ctx.redis, args).await
+    }
+}
+
+/// Writes the chosen version back as a new version, so the revert itself
+/// shows up in the history and can be undone.
+pub async fn revert_knowledge(
+    redis: &RedisManager,
+    params: RevertKnowledgeArgs,
+) -> Result<KnowledgeActionResult> {
+    let mut conn = redis.get_connection().await?;
+
+    // As with updates, a concurrent change sends us round again
+    for _ in 0..SAVE_ATTEMPTS {
+        let previous = get_knowledge(redis, &params.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Knowledge entry {} not found", params.id))?;
+        if params.version == previous.version {
+            return Err(anyhow::anyhow!("Knowledge entry {} is already at version {}", previous.id, previous.version));
+        }
+
+        let target = load_history(&mut conn, &previous)
+            .await?
+            .into_iter()
+            .find(|v| v.version == params.version)
+            .ok_or_else(|| anyhow::anyhow!("Knowledge entry {} has no version {}", previous.id, params.version))?;
+
+        let entry = KnowledgeEntry {
+            content: target.content,
+            tags: target.tags,
+            metadata: target.metadata,
+            updated_at: chrono::Utc::now().timestamp(),
+            version: previous.version + 1,
+            ..previous.clone()
+        };
+
+        if save_version(&mut conn, Some(&previous), &entry, &params.agent_id, Some(target.version)).await? {
+            return Ok(KnowledgeActionResult {
+                message: format!(
+                    "Knowledge entry {} reverted to version {} as version {} by {}",
+                    entry.id, target.version, entry.version, params.agent_id
+                ),
+                id: entry.id,
+            });
+        }
+    }
+
+    Err(anyhow::anyhow!("Knowledge entry {} kept changing while it was reverted; try again", params.id))
+}
+
+pub struct DeleteKnowledge;
+
+#[async_trait]
+impl Tool for DeleteKnowledge {
+    const NAME: &'static str = "delete_knowledge";
+    const DESCRIPTION: &'static str =
+        "Delete a knowledge entry; only its author or a knowledge admin may do so";
+
+    type Args = DeleteKnowledgeArgs;
+    type Output = KnowledgeActionResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: DeleteKnowledgeArgs) -> Result<KnowledgeActionResult> {
+        delete_knowledge(ctx.redis, args).await
+    }
+}
+
+pub async fn delete_knowledge(
+    redis: &RedisManager,
+    params: DeleteKnowledgeArgs,
+) -> Result<KnowledgeActionResult> {
+    // Checked and deleted in one script, so a concurrent upsert cannot land
+    // between the author check, the deletes and the index cleanup
+    let mut conn = redis.get_connection().await?;
+    let is_admin = get_knowledge_admins().contains(&params.agent_id);
+    let outcome: Option<(i32, String)> =
+        redis::Script::new(include_str!("../scripts/delete_knowledge.lua"))
+            .key(format!("knowledge:{}", params.id))
+            .key(KNOWLEDGE_KEYS)
+            .key(history_key(&params.id))
+            .arg(&params.id)
+            .arg(&params.agent_id)
+            .arg(if is_admin { "1" } else { "0" })
+            .invoke_async(&mut conn)
+            .await?;
+
+    match outcome {
+        None => Err(anyhow::anyhow!("Knowledge entry {} not found", params.id)),
+        Some((0, author)) => Err(anyhow::anyhow!(
+            "Knowledge entry {} belongs to agent {}; only it or a knowledge admin can delete it",
+            params.id,
+            author
+        )),
+        Some(_) => Ok(KnowledgeActionResult {
+            message: format!("Knowledge entry {} deleted by {}", params.id, params.agent_id),
+            id: params.id,
+        }),
+    }
+}
+
+pub struct SearchKnowledge;
+
+#[async_trait]
+impl Tool for SearchKnowledge {
+    const NAME: &'static str = "search_knowledge";
+    const DESCRIPTION: &'static str =
+        "RAG search across all stored knowledge using semantic queries";
+    // Not read-only, as every search bumps the access_count of what it
+    // returns, but a count left half bumped by an aborted search is harmless
+    const ABORTABLE: bool = true;
+
+    type Args = SearchKnowledgeArgs;
+    type Output = SearchKnowledgeResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: SearchKnowledgeArgs) -> Result<SearchKnowledgeResult> {
+        search_knowledge(ctx.redis, args).await
+    }
 }
 
 pub async fn search_knowledge(
     redis: &RedisManager,
-    args: Value,
-) -> Result<String> {
-    let params: SearchKnowledgeArgs = serde_json::from_value(args)?;
-    
+    params: SearchKnowledgeArgs,
+) -> Result<SearchKnowledgeResult> {
     // Ensure search index exists
     if let Err(e) = ensure_index(redis).await {
-        eprintln!("Warning: Failed to create search index: {}", e);
+        warn!("Failed to create search index: {}", e);
     }
 
     // Use search index
     let index = SearchIndex::new("knowledge-idx");
-    let results = index.search(redis, &params).await?;
+    let results: SearchKnowledgeResult = serde_json::from_value(index.search(redis, &params).await?)?;
     
     // Update access counts for returned entries
     let mut conn = redis.get_connection().await?;
-    if let Some(entries) = results.get("results").and_then(|v| v.as_array()) {
-        for entry in entries {
-            if let Some(id) = entry.get("id").and_then(|v| v.as_str()) {
-                let key = format!("knowledge:{}", id);
-                let _: () = redis::cmd("JSON.NUMINCRBY")
-                    .arg(&key)
-                    .arg("$.access_count")
-                    .arg(1)
-                    .query_async(&mut conn)
-                    .await?;
-            }
+    for entry in &results.results {
+        if let Some(id) = entry.get("id").and_then(|v| v.as_str()) {
+            let key = format!("knowledge:{}", id);
+            let _: () = redis::cmd("JSON.NUMINCRBY")
+                .arg(&key)
+                .arg("$.access_count")
+                .arg(1)
+                .query_async(&mut conn)
+                .await?;
         }
     }
     
-    Ok(results.to_string())
+    Ok(results)
+}
+
+pub struct LearnFromAgents;
+
+#[async_trait]
+impl Tool for LearnFromAgents {
+    const NAME: &'static str = "learn_from_agents";
+    const DESCRIPTION: &'static str =
+        "Query what other agents learned about specific topics or errors";
+    // Searches like search_knowledge, so the same goes for it
+    const ABORTABLE: bool = true;
+
+    type Args = LearnFromAgentsArgs;
+    type Output = LearningsResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: LearnFromAgentsArgs) -> Result<LearningsResult> {
+        learn_from_agents(ctx.redis, ctx.client, args).await
+    }
 }
 
 pub async fn learn_from_agents(
     redis: &RedisManager,
-    args: Value,
-) -> Result<String> {
-    let topic = args.get("topic")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Topic required"))?;
+    client: &Notifier,
+    params: LearnFromAgentsArgs,
+) -> Result<LearningsResult> {
+    let topic = params.topic.as_str();
     
-    let _time_range = args.get("time_range")
-        .and_then(|v| v.as_str())
-        .unwrap_or("all");
+    let since = range_start(params.time_range.unwrap_or(TimeRange::All), chrono::Utc::now().timestamp());
     
-    // Search for knowledge entries related to the topic
-    let search_args = json!({
-        "query": topic,
-        "limit": 20
-    });
+    // Search for knowledge entries related to the topic, narrowed to the
+    // error when one is given
+    let query = match &params.error_pattern {
+        Some(pattern) => format!("{} {}", topic, pattern),
+        None => topic.to_string(),
+    };
+    let search_args = SearchKnowledgeArgs {
+        query,
+        limit: Some(20),
+        ..Default::default()
+    };
     
-    let search_results = search_knowledge(redis, search_args).await?;
-    let results: Value = serde_json::from_str(&search_results)?;
+    let mut results = search_knowledge(redis, search_args).await?;
+    if let Some(since) = since {
+        results.results.retain(|entry| {
+            let changed_at = entry.get("updated_at").or_else(|| entry.get("created_at"));
+            changed_at.and_then(|t| t.as_i64()).is_some_and(|t| t >= since)
+        });
+    }
     
     // Group by agent and extract learnings
-    let mut learnings = json!({
-        "topic": topic,
-        "agent_learnings": {},
-        "common_patterns": [],
-        "error_solutions": []
-    });
+    let mut learnings = LearningsResult {
+        topic: topic.to_string(),
+        agent_learnings: HashMap::new(),
+        common_patterns: Vec::new(),
+        error_solutions: Vec::new(),
+        distilled_by: None,
+    };
+    for entry in &results.results {
+        let agent_id = entry.get("agent_id").and_then(|v| v.as_str()).unwrap_or("unknown");
+        learnings.agent_learnings.entry(agent_id.to_string()).or_default().push(entry.clone());
+    }
     
-    if let Some(entries) = results.get("results").and_then(|v| v.as_array()) {
-        for entry in entries {
-            if let Some(category) = entry.get("category").and_then(|v| v.as_str()) {
-                if category == "errors" || category == "solutions" {
-                    learnings["error_solutions"].as_array_mut().unwrap().push(entry.clone());
-                }
+    // Let the client's model find the patterns if it can
+    if client.client_supports("sampling") && !results.results.is_empty() {
+        match distill(client, topic, &results.results).await {
+            Ok((distilled, model)) => {
+                learnings.common_patterns = distilled.common_patterns;
+                learnings.error_solutions = distilled.error_solutions;
+                learnings.distilled_by = Some(model);
+                return Ok(learnings);
+            }
+            Err(e) => warn!("Sampling failed, returning raw learnings: {}", e),
+        }
+    }
+    
+    for entry in results.results {
+        if let Some(category) = entry.get("category").and_then(|v| v.as_str()) {
+            if category == "errors" || category == "solutions" {
+                learnings.error_solutions.push(entry.clone());
             }
         }
     }
     
-    Ok(learnings.to_string())
-}
\ No newline at end of file
+    Ok(learnings)
+}
+
+/// The earliest timestamp within `range` of `now`, or `None` for all time.
+fn range_start(range: TimeRange, now: i64) -> Option<i64> {
+    let seconds = match range {
+        TimeRange::Hour => 3600,
+        TimeRange::Day => 86400,
+        TimeRange::Week => 86400 * 7,
+        TimeRange::All => return None,
+    };
+    Some(now - seconds)
+}
+
+/// Asks the client's model (via `sampling/createMessage`) to distill
+/// `entries` into common patterns and error/solution pairs. Returns them
+/// with the name of the model that answered.
+async fn distill(client: &Notifier, topic: &str, entries: &[Value]) -> Result<(DistilledLearnings, String)> {
+    let prompt = format!(
+        "Agents stored these knowledge entries about \"{}\":\n\n{}\n\n\
+         Distill them into the patterns that keep coming up and the errors that were hit with \
+         the solution that fixed each one. Reply with only a JSON object of the form \
+         {{\"common_patterns\": [\"...\"], \"error_solutions\": [{{\"error\": \"...\", \"solution\": \"...\"}}]}}.",
+        topic,
+        serde_json::to_string_pretty(entries)?
+    );
+
+    let result = client.request("sampling/createMessage", json!({
+        "messages": [{
+            "role": "user",
+            "content": {"type": "text", "text": prompt}
+        }],
+        "systemPrompt": "You summarize what software agents learned while working. You answer in JSON.",
+        "includeContext": "none",
+        "maxTokens": 1024
+    })).await?;
+
+    let text = result["content"]["text"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Sampling returned no text"))?;
+    // Models like to wrap JSON in a code fence
+    let json = match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &text[start..=end],
+        _ => text,
+    };
+    let distilled: DistilledLearnings = serde_json::from_str(json)?;
+    let model = result["model"].as_str().unwrap_or("unknown").to_string();
+    Ok((distilled, model))
+}
@@ -1,6 +1,10 @@
+pub mod assignment;
 pub mod database;
 pub mod heartbeat;
 pub mod memory;
+pub mod registry;
 pub mod search;
 pub mod tasks;
-pub mod trello;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,191 @@
+use crate::backends::TaskBackend;
+use crate::notifications::{Notifier, Progress};
+use crate::utils::RedisManager;
+use anyhow::Result;
+use async_trait::async_trait;
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::{database, heartbeat, memory, tasks};
+
+/// Arguments that do not match the tool's argument struct; maps to JSON-RPC
+/// code -32602.
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid arguments for {tool}: {reason}")]
+pub struct InvalidArguments {
+    pub tool: String,
+    pub reason: String,
+}
+
+/// No tool registered under the requested name; maps to JSON-RPC code -32601.
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown tool: {0}")]
+pub struct UnknownTool(pub String);
+
+/// Everything a tool call can reach.
+pub struct ToolContext<'a> {
+    pub redis: &'a RedisManager,
+    pub backend: &'a dyn TaskBackend,
+    pub progress: &'a Progress,
+    /// The calling session, for sampling or elicitation requests
+    pub client: &'a Notifier,
+}
+
+/// A tool exposed through `tools/list` and `tools/call`.
+///
+/// `Args` is both what the call's `arguments` are deserialized into and
+/// where the advertised `inputSchema` comes from, so the two cannot drift
+/// apart. Doc comments on its fields become the property descriptions.
+/// `Output` likewise backs `outputSchema` and is returned to the client as
+/// `structuredContent`; it must serialize to a JSON object.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+    /// Whether the tool leaves the board and stored knowledge as it found
+    /// them; advertised as `readOnlyHint`.
+    const READ_ONLY: bool = false;
+    /// Whether a call can be aborted at any await point when the client
+    /// cancels it. Calls that cannot run to the end and have their response
+    /// dropped.
+    const ABORTABLE: bool = Self::READ_ONLY;
+
+    type Args: DeserializeOwned + JsonSchema + Send;
+    type Output: Serialize + JsonSchema + Send;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: Self::Args) -> Result<Self::Output>;
+}
+
+/// Object-safe face of [`Tool`] so differently typed tools can share a
+/// registry.
+#[async_trait]
+trait RegisteredTool: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn abortable(&self) -> bool;
+
+    fn definition(&self) -> Value;
+
+    async fn call_json(&self, ctx: &ToolContext<'_>, arguments: Value) -> Result<Value>;
+}
+
+#[async_trait]
+impl<T: Tool> RegisteredTool for T {
+    fn name(&self) -> &'static str {
+        T::NAME
+    }
+
+    fn abortable(&self) -> bool {
+        T::ABORTABLE
+    }
+
+    fn definition(&self) -> Value {
+        json!({
+            "name": T::NAME,
+            "description": T::DESCRIPTION,
+            "inputSchema": object_schema::<T::Args>(),
+            "outputSchema": object_schema::<T::Output>(),
+            "annotations": {"readOnlyHint": T::READ_ONLY}
+        })
+    }
+
+    async fn call_json(&self, ctx: &ToolContext<'_>, arguments: Value) -> Result<Value> {
+        // `arguments` may be left out entirely for tools that take none
+        let arguments = if arguments.is_null() { json!({}) } else { arguments };
+        let args = serde_json::from_value(arguments).map_err(|e| InvalidArguments {
+            tool: T::NAME.to_string(),
+            reason: e.to_string(),
+        })?;
+        let output = self.call(ctx, args).await?;
+        Ok(serde_json::to_value(output)?)
+    }
+}
+
+/// JSON Schema for `T` with every type inlined, as MCP clients expect a
+/// self-contained object schema.
+fn object_schema<T: JsonSchema>() -> Value {
+    let generator = SchemaSettings::draft07()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+            s.meta_schema = None;
+        })
+        .into_generator();
+    let mut schema = serde_json::to_value(generator.into_root_schema_for::<T>())
+        .unwrap_or_else(|_| json!({"type": "object"}));
+
+    // The Rust type name is noise to clients
+    if let Some(schema) = schema.as_object_mut() {
+        schema.remove("title");
+    }
+    schema
+}
+
+/// The tools this server offers, in the order `tools/list` pages through them.
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn RegisteredTool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self { tools: Vec::new() }
+    }
+
+    /// Every built-in tool.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        // Task management
+        registry.register(tasks::ScanTasks);
+        registry.register(tasks::TakeTask);
+        registry.register(tasks::UpdateTask);
+        registry.register(tasks::ReleaseTask);
+        registry.register(tasks::CompleteTask);
+        // Memory and learning
+        registry.register(memory::StoreKnowledge);
+        registry.register(memory::UpdateKnowledge);
+        registry.register(memory::GetKnowledgeHistory);
+        registry.register(memory::RevertKnowledge);
+        registry.register(memory::DeleteKnowledge);
+        registry.register(memory::SearchKnowledge);
+        registry.register(memory::LearnFromAgents);
+        // Heartbeat and coordination
+        registry.register(heartbeat::Heartbeat);
+        registry.register(heartbeat::CheckAgentStatus);
+        // Advanced database operations
+        registry.register(database::RagQuery);
+        registry
+    }
+
+    pub fn register<T: Tool + 'static>(&mut self, tool: T) {
+        self.tools.push(Box::new(tool));
+    }
+
+    /// Tool definitions for `tools/list`.
+    pub fn list(&self) -> Vec<Value> {
+        self.tools.iter().map(|t| t.definition()).collect()
+    }
+
+    /// Whether tool `name` exists and its calls can be aborted.
+    pub fn is_abortable(&self, name: &str) -> bool {
+        self.tools.iter().any(|t| t.name() == name && t.abortable())
+    }
+
+    /// Runs tool `name`, returning its structured result.
+    pub async fn call(&self, name: &str, ctx: &ToolContext<'_>, arguments: Value) -> Result<Value> {
+        let tool = self
+            .tools
+            .iter()
+            .find(|t| t.name() == name)
+            .ok_or_else(|| UnknownTool(name.to_string()))?;
+        tool.call_json(ctx, arguments).await
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,8 @@
 use crate::schemas::*;
+use crate::pagination;
 use crate::utils::RedisManager;
 use anyhow::{anyhow, Result};
-use redis::{AsyncCommands, FromRedisValue, RedisResult};
+use redis::RedisResult;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
@@ -10,6 +11,7 @@ const DEFAULT_LANGUAGE: &str = "english";
 const DEFAULT_SCORE_FIELD: &str = "_score";
 const KNOWLEDGE_PREFIX: &str = "knowledge:";
 const DEFAULT_FUZZY_DISTANCE: u32 = 2;
+#[allow(dead_code)]
 const MAX_EXPANSIONS: u32 = 50;
 
 // Search index types
@@ -19,6 +21,7 @@ pub enum IndexType {
     Tag,
     Numeric,
     Vector,
+    #[allow(dead_code)]
     Geo,
 }
 
@@ -67,6 +70,7 @@ impl IndexField {
 }
 
 // Extended search parameters
+#[allow(dead_code)]
 pub struct SearchParams {
     pub query: String,
     pub filters: Vec<(String, String)>,
@@ -128,11 +132,13 @@ impl QueryBuilder {
         self
     }
 
+    #[allow(dead_code)]
     pub fn tag_filter(mut self, field: &str, value: &str) -> Self {
         self.parts.push(format!("@{}:{{{}}}", field, value));
         self
     }
 
+    #[allow(dead_code)]
     pub fn numeric_range(mut self, field: &str, min: f64, max: f64) -> Self {
         self.parts.push(format!("@{}:[{} {}]", field, min, max));
         self
@@ -211,14 +217,19 @@ impl SearchIndex {
         args
     }
 
+    /// Creates the index unless it already exists. Recreating it would have
+    /// Redis re-scan every entry, and searches running meanwhile would page
+    /// over a half-built index.
     pub async fn create(&self, redis: &RedisManager) -> Result<()> {
         let mut conn = redis.get_connection().await?;
 
-        // Drop existing index if it exists (ignore errors if it doesn't)
-        let _: RedisResult<()> = redis::cmd("FT.DROPINDEX")
+        let existing: RedisResult<redis::Value> = redis::cmd("FT.INFO")
             .arg(&self.name)
             .query_async(&mut conn)
             .await;
+        if existing.is_ok() {
+            return Ok(());
+        }
 
         // Define fields with advanced options
         let fields = vec![
@@ -280,7 +291,7 @@ pub async fn search(
         }
         
         // Ensure Redis search is initialized
-        if !redis.initialized {
+        if !redis.is_initialized() {
             return Err(anyhow!("Redis search not initialized"));
         }
         // Convert standard args to extended search params
@@ -290,13 +301,16 @@ pub async fn search(
             "category".to_string(),
             "content".to_string(),
         ]);
+
+        // Results come in ranking order, so the cursor is an offset into it
+        let offset = pagination::offset(params.cursor.as_deref())?;
         
         let search_params = SearchParams {
             query: params.query.clone(),
             filters: vec![],
             numeric_filters: vec![],
             limit: params.limit,
-            offset: Some(0),
+            offset: Some(offset),
             sort_by: Some("created_at".to_string()),
             sort_asc: false,
             min_score: None,
@@ -306,7 +320,12 @@ pub async fn search(
             fuzzy_distance: Some(DEFAULT_FUZZY_DISTANCE),
         };
 
-        self.advanced_search(redis, &search_params).await
+        let mut results = self.advanced_search(redis, &search_params).await?;
+        let seen = offset + results["count"].as_u64().unwrap_or(0) as usize;
+        if seen < results["total"].as_u64().unwrap_or(0) as usize {
+            results["next_cursor"] = json!(pagination::offset_cursor(seen));
+        }
+        Ok(results)
     }
 
 pub async fn advanced_search(
@@ -320,7 +339,7 @@ pub async fn advanced_search(
         }
         
         // Ensure Redis search is initialized 
-        if !redis.initialized {
+        if !redis.is_initialized() {
             return Err(anyhow!("Redis search not initialized"));
         }
         
@@ -354,7 +373,8 @@ pub async fn advanced_search(
                     return Ok(json!({
                         "query": params.query,
                         "results": entries,
-                        "count": entries.len()
+                        "count": entries.len(),
+                        "total": results.0
                     }));
                 }
                 Err(e) => {
@@ -1,225 +1,524 @@
+use crate::backends::TaskBackend;
+use crate::notifications::{Notifier, Progress};
+use crate::pagination;
 use crate::schemas::*;
-use crate::utils::{RedisManager, get_trello_config, get_trello_list_ids};
+use crate::tools::assignment;
+use crate::tools::registry::{InvalidArguments, Tool, ToolContext};
+use crate::utils::{RedisManager, get_heartbeat_timeout, get_lease_timeout, get_page_size};
 use anyhow::Result;
+use async_trait::async_trait;
+use log::warn;
 use redis::AsyncCommands;
 use serde_json::{json, Value};
 
+pub struct ScanTasks;
+
+#[async_trait]
+impl Tool for ScanTasks {
+    const NAME: &'static str = "scan_trello_tasks";
+    const DESCRIPTION: &'static str =
+        "List task cards on the configured board (Trello, local task files or GitHub issues) with their assignment status";
+    const READ_ONLY: bool = true;
+
+    type Args = ScanTasksArgs;
+    type Output = ScanTasksResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: ScanTasksArgs) -> Result<ScanTasksResult> {
+        scan_trello_tasks(ctx.redis, ctx.backend, args, ctx.progress).await
+    }
+}
+
 pub async fn scan_trello_tasks(
     redis: &RedisManager,
-    client: &reqwest::Client,
-    args: Value,
-) -> Result<String> {
-    let (key, token, board_id) = get_trello_config();
-    
-    let url = format!(
-        "https://api.trello.com/1/boards/{board_id}/cards"
-    );
-    
-    println!("DEBUG - Trello API URL: {}", url);
-    println!("DEBUG - Trello key: {}", key);
-    println!("DEBUG - Trello token: {}", token);
-    
-    let response = client.get(&url)
-        .header("Accept", "application/json")
-        .query(&[
-            ("key", &key),
-            ("token", &token),
-        ])
-        .send()
-        .await?;
-    
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to get cards: {}", response.status()));
-    }
-    
-    let response_text = response.text().await?;
-    let mut cards: Vec<TrelloCard> = match serde_json::from_str(&response_text) {
-        Ok(cards) => cards,
-        Err(e) => {
-            println!("Failed to parse response: {}", e);
-            println!("Response text: {}", response_text);
-            return Err(anyhow::anyhow!("Failed to parse card list: {}", e));
-        }
+    backend: &dyn TaskBackend,
+    params: ScanTasksArgs,
+    progress: &Progress,
+) -> Result<ScanTasksResult> {
+    // Closed cards are only fetched when the done list is asked for
+    let list_filter = params.list_filter.unwrap_or(ListFilter::All);
+    let mut cards = match list_filter {
+        ListFilter::Done => backend.list_cards().await?,
+        _ => backend.list_open_cards().await?,
     };
+    progress.report(1, None, &format!("Fetched {} cards", cards.len()));
     
     // Filter by list if specified
-    if let Some(list_filter) = args.get("list_filter").and_then(|v| v.as_str()) {
-        let (todo_list_id, in_progress_list_id, done_list_id) = get_trello_list_ids();
-        cards.retain(|card| {
-            match list_filter {
-                "todo" => !card.closed && card.id_list == todo_list_id,
-                "in_progress" => !card.closed && card.id_list == in_progress_list_id,
-                "done" => card.closed || card.id_list == done_list_id,
-                "all" => true,
-                _ => true
-            }
-        });
-    }
+    let lists = backend.workflow_lists();
+    cards.retain(|card| {
+        match list_filter {
+            ListFilter::Todo => !card.closed && card.list_id == lists.todo,
+            ListFilter::InProgress => !card.closed && card.list_id == lists.in_progress,
+            ListFilter::Done => card.closed || card.list_id == lists.done,
+            ListFilter::All => true,
+        }
+    });
+
+    // Page in board order, so the highest-priority cards come first, but
+    // resume after the last card seen so moves before it shift nothing
+    let matching = cards.len();
+    let page_size = params.limit.filter(|&n| n > 0).unwrap_or_else(get_page_size);
+    let (cards, next_cursor) =
+        pagination::paginate_after_in_order(cards, |card| card.id.clone(), params.cursor.as_deref(), page_size)?;
     
     // Get agent assignments and list details from Redis
     let mut conn = redis.get_connection().await?;
     let mut enriched_cards = Vec::new();
 
     // Get list details for context
-    let lists_url = format!(
-        "https://api.trello.com/1/boards/{}/lists?key={}&token={}",
-        board_id, key, token
-    );
-    let lists: Vec<TrelloList> = client.get(&lists_url).send().await?.json().await?;
+    let lists = backend.list_lists().await?;
     
-    for card in cards {
-        let assignment_key = format!("assignment:{}", card.id);
-        let agent_id: Option<String> = conn.get(&assignment_key).await?;
-        
-        let status = if agent_id.is_some() {
-            "assigned"
-        } else {
-            "available"
-        };
+    // Two backend round trips plus one assignment lookup per card
+    let total = 2 + cards.len();
+    progress.report(2, Some(total), "Fetched lists");
+    
+    for (done, card) in cards.into_iter().enumerate() {
+        let agent_id = assignment::get(&mut conn, &card.id)
+            .await?
+            .map(|a| a.agent_id);
         
-        // Find list name
-        let list_name = lists.iter()
-            .find(|l| l.id == card.id_list)
-            .map(|l| l.name.clone())
-            .unwrap_or_else(|| "Unknown List".to_string());
-
-        enriched_cards.push(json!({
-            "id": card.id,
-            "name": card.name,
-            "description": card.desc,
-            "list_id": card.id_list,
-            "list_name": list_name,
-            "status": status,
-            "assigned_to": agent_id,
-            "url": card.url,
-            "short_url": card.short_url,
-            "due": card.due,
-            "due_complete": card.due_complete,
-            "labels": card.labels,
-            "members": card.id_members
-        }));
+        enriched_cards.push(scanned_card(card, &lists, agent_id));
+        progress.report(3 + done, Some(total), "Checking assignments");
     }
     
-    Ok(json!({
-        "cards": enriched_cards,
-        "total": enriched_cards.len(),
-        "lists": lists,
-        "filters_applied": args.get("list_filter").and_then(|v| v.as_str()).unwrap_or("all")
-    }).to_string())
+    Ok(ScanTasksResult {
+        total: matching,
+        cards: enriched_cards,
+        lists,
+        filters_applied: list_filter,
+        next_cursor,
+    })
+}
+
+/// `card` as `scan_trello_tasks` reports it, given the board's lists and
+/// the agent holding it, if any.
+pub fn scanned_card(card: TaskCard, lists: &[TaskList], assigned_to: Option<String>) -> ScannedCard {
+    let status = if assigned_to.is_some() {
+        CardStatus::Assigned
+    } else {
+        CardStatus::Available
+    };
+    
+    // Find list name
+    let list_name = lists.iter()
+        .find(|l| l.id == card.list_id)
+        .map(|l| l.name.clone())
+        .unwrap_or_else(|| "Unknown List".to_string());
+
+    ScannedCard {
+        id: card.id,
+        name: card.name,
+        description: card.description,
+        list_id: card.list_id,
+        list_name,
+        status,
+        assigned_to,
+        url: card.url,
+        short_url: card.short_url,
+        due: card.due,
+        due_complete: card.due_complete,
+        labels: card.labels,
+        members: card.members,
+    }
+}
+
+pub struct TakeTask;
+
+#[async_trait]
+impl Tool for TakeTask {
+    const NAME: &'static str = "take_trello_task";
+    const DESCRIPTION: &'static str =
+        "Claim a task before working on it. Returns a lease_token that must be passed to later updates";
+
+    type Args = TakeTaskArgs;
+    type Output = TakeTaskResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: TakeTaskArgs) -> Result<TakeTaskResult> {
+        take_trello_task(ctx.redis, ctx.backend, ctx.client, args).await
+    }
 }
 
 pub async fn take_trello_task(
     redis: &RedisManager,
-    client: &reqwest::Client,
-    args: Value,
-) -> Result<String> {
-    let params: TakeTaskArgs = serde_json::from_value(args)?;
+    backend: &dyn TaskBackend,
+    client: &Notifier,
+    params: TakeTaskArgs,
+) -> Result<TakeTaskResult> {
     let mut conn = redis.get_connection().await?;
     
-    // Check if task is already assigned
-    let assignment_key = format!("assignment:{}", params.card_id);
-    let existing: Option<String> = conn.get(&assignment_key).await?;
+    // SET NX would happily claim a card that does not exist
+    backend.get_card(&params.card_id).await?;
     
-    if existing.is_some() {
-        return Err(anyhow::anyhow!("Task already assigned to another agent"));
-    }
+    // Claim the task atomically; only one agent can win the SET NX
+    let (lease, previous_owner) = match assignment::claim(&mut conn, &params.card_id, &params.agent_id).await? {
+        Some(lease) => (lease, None),
+        None => match take_over_stale(&mut conn, client, &params).await? {
+            Some((lease, owner)) => (lease, Some(owner)),
+            None => {
+                let owner = assignment::get(&mut conn, &params.card_id)
+                    .await?
+                    .map(|a| a.agent_id)
+                    .unwrap_or_else(|| "another agent".to_string());
+                return Err(anyhow::anyhow!("Task already assigned to {}", owner));
+            }
+        },
+    };
     
-    // Assign task
-    let _: () = conn.set_ex(&assignment_key, &params.agent_id, 3600).await?;
+    // The caller never sees the lease token if we fail from here on, so give
+    // the claim back rather than leave the card locked until it lapses
+    if let Err(e) = announce_claim(&mut conn, backend, &params, previous_owner.as_deref()).await {
+        if let Err(release_error) =
+            assignment::release(&mut conn, &params.card_id, &params.agent_id, &lease.lease_token).await
+        {
+            warn!("Failed to release claim on task {}: {}", params.card_id, release_error);
+        }
+        return Err(e);
+    }
     
+    Ok(TakeTaskResult {
+        message: format!("Task {} successfully assigned to agent {}", params.card_id, params.agent_id),
+        card_id: params.card_id,
+        agent_id: params.agent_id,
+        lease_token: lease.lease_token,
+        expires_in: get_lease_timeout(),
+    })
+}
+
+/// Records a fresh claim: adds the card to the agent's active tasks and
+/// tells the tracker, noting the agent it was taken over from if any.
+async fn announce_claim(
+    conn: &mut redis::aio::Connection,
+    backend: &dyn TaskBackend,
+    params: &TakeTaskArgs,
+    previous_owner: Option<&str>,
+) -> Result<()> {
     // Add to agent's active tasks
     let agent_tasks_key = format!("agent:{}:tasks", params.agent_id);
     let _: () = conn.sadd(&agent_tasks_key, &params.card_id).await?;
     
-    // Add comment to Trello card
-    let (key, token, _) = get_trello_config();
-    let comment_url = format!(
-        "https://api.trello.com/1/cards/{}/actions/comments?key={}&token={}",
-        params.card_id, key, token
-    );
+    if let Some(previous_owner) = previous_owner {
+        let comment = format!(
+            "Task taken over from unresponsive agent {} by agent {}",
+            previous_owner, params.agent_id
+        );
+        backend.add_comment(&params.card_id, &comment).await?;
+        record_update(conn, &params.card_id, json!({
+            "agent_id": params.agent_id,
+            "type": "takeover",
+            "outcome": "reclaimed",
+            "content": comment,
+            "timestamp": chrono::Utc::now().timestamp()
+        })).await?;
+    }
     
-    let comment_body = json!({
-        "text": format!("Task claimed by agent: {}", params.agent_id)
-    });
+    // Let the tracker know (comment on the card, etc.)
+    backend.on_claimed(&params.card_id, &params.agent_id).await
+}
+
+/// When `params.card_id` is held by an agent that stopped sending heartbeats
+/// (but the reaper has not got to it yet), asks the caller through
+/// elicitation whether to take the task over, and claims it if they agree.
+/// Returns the new lease and the agent it was taken from, or `None` if the
+/// owner is still alive, the client cannot be asked or the caller declined.
+async fn take_over_stale(
+    conn: &mut redis::aio::Connection,
+    client: &Notifier,
+    params: &TakeTaskArgs,
+) -> Result<Option<(TaskAssignment, String)>> {
+    let owner = match assignment::get(conn, &params.card_id).await? {
+        Some(owner) if owner.agent_id != params.agent_id => owner,
+        _ => return Ok(None),
+    };
     
-    client.post(&comment_url).json(&comment_body).send().await?;
+    let cutoff = chrono::Utc::now().timestamp() - get_heartbeat_timeout() as i64;
+    let heartbeat_key = format!("heartbeat:{}:{}", owner.agent_id, params.card_id);
+    let alive: bool = conn.exists(&heartbeat_key).await?;
+    if alive || owner.claimed_at > cutoff || !client.client_supports("elicitation") {
+        return Ok(None);
+    }
+    
+    let message = format!(
+        "Task {} is assigned to agent {}, which has not sent a heartbeat for it in over {} seconds. \
+         Take the task over as agent {}?",
+        params.card_id, owner.agent_id, get_heartbeat_timeout(), params.agent_id
+    );
+    let answer = client.elicit(&message, json!({
+        "type": "object",
+        "properties": {
+            "take_over": {
+                "type": "boolean",
+                "title": "Take over the task",
+                "description": "The other agent loses its lease and any further updates from it are rejected"
+            }
+        },
+        "required": ["take_over"]
+    })).await?;
+    if answer.and_then(|a| a["take_over"].as_bool()) != Some(true) {
+        return Ok(None);
+    }
     
-    Ok(format!("Task {} successfully assigned to agent {}", params.card_id, params.agent_id))
+    // The owner may have come back while we were asking; revoke re-checks
+    if !assignment::revoke(conn, &params.card_id, &owner.agent_id, cutoff).await? {
+        return Ok(None);
+    }
+    let lease = assignment::claim(conn, &params.card_id, &params.agent_id).await?;
+    Ok(lease.map(|lease| (lease, owner.agent_id)))
+}
+
+pub struct UpdateTask;
+
+#[async_trait]
+impl Tool for UpdateTask {
+    const NAME: &'static str = "update_trello_task";
+    const DESCRIPTION: &'static str =
+        "Record task progress: add a comment or checklist item, replace the description or move the card to another list";
+
+    type Args = UpdateTaskArgs;
+    type Output = TaskActionResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: UpdateTaskArgs) -> Result<TaskActionResult> {
+        update_trello_task(ctx.redis, ctx.backend, ctx.client, args).await
+    }
 }
 
 pub async fn update_trello_task(
     redis: &RedisManager,
-    client: &reqwest::Client,
-    args: Value,
-) -> Result<String> {
-    let params: UpdateTaskArgs = serde_json::from_value(args)?;
-    let (key, token, _) = get_trello_config();
-    
-    match params.update_type.as_str() {
-        "comment" => {
-            let url = format!(
-                "https://api.trello.com/1/cards/{}/actions/comments?key={}&token={}",
-                params.card_id, key, token
-            );
-            
-            let body = json!({
-                "text": format!("[Agent {}] {}", params.agent_id, params.content)
-            });
-            
-            client.post(&url).json(&body).send().await?;
+    backend: &dyn TaskBackend,
+    client: &Notifier,
+    params: UpdateTaskArgs,
+) -> Result<TaskActionResult> {
+    let mut conn = redis.get_connection().await?;
+    
+    // Only the agent holding the lease may mutate the card
+    assignment::verify_lease(&mut conn, &params.card_id, &params.agent_id, &params.lease_token).await?;
+    
+    match params.update_type {
+        UpdateType::Comment => {
+            let text = format!("[Agent {}] {}", params.agent_id, params.content);
+            backend.add_comment(&params.card_id, &text).await?;
         }
-        "checklist" => {
-            // Create or update checklist
-            let url = format!(
-                "https://api.trello.com/1/cards/{}/checklists?key={}&token={}",
-                params.card_id, key, token
-            );
-            
-            let body = json!({
-                "name": "Progress",
-                "pos": "bottom"
-            });
-            
-            client.post(&url).json(&body).send().await?;
+        UpdateType::Checklist => {
+            backend.add_checklist_item(&params.card_id, &params.content).await?;
         }
-        "description" => {
-            let url = format!(
-                "https://api.trello.com/1/cards/{}?key={}&token={}",
-                params.card_id, key, token
-            );
-            
-            let body = json!({
-                "desc": params.content
-            });
-            
-            client.put(&url).json(&body).send().await?;
+        UpdateType::Description => {
+            backend.update_description(&params.card_id, &params.content).await?;
         }
-        "move_list" => {
-            let url = format!(
-                "https://api.trello.com/1/cards/{}?key={}&token={}",
-                params.card_id, key, token
-            );
-            
-            let body = json!({
-                "idList": params.list_id.unwrap_or_default()
-            });
-            
-            client.put(&url).json(&body).send().await?;
+        UpdateType::MoveList => {
+            let list_id = match &params.list_id {
+                Some(list_id) => list_id.clone(),
+                None => {
+                    let list_id = choose_list(backend, client, &params.card_id).await?;
+                    // The user may take a while to answer; the lease could
+                    // have lapsed and gone to another agent meanwhile
+                    assignment::verify_lease(&mut conn, &params.card_id, &params.agent_id, &params.lease_token).await?;
+                    list_id
+                }
+            };
+            backend.move_card(&params.card_id, &list_id).await?;
         }
-        _ => return Err(anyhow::anyhow!("Invalid update type")),
     }
     
     // Store update in Redis for tracking
-    let mut conn = redis.get_connection().await?;
-    let update_key = format!("updates:{}:{}", params.card_id, chrono::Utc::now().timestamp());
-    let update_data = json!({
+    record_update(&mut conn, &params.card_id, json!({
         "agent_id": params.agent_id,
         "type": params.update_type,
         "content": params.content,
         "timestamp": chrono::Utc::now().timestamp()
-    });
+    })).await?;
+    
+    Ok(TaskActionResult {
+        message: format!("Task {} updated successfully", params.card_id),
+        card_id: params.card_id,
+        agent_id: params.agent_id,
+    })
+}
+
+/// Asks the caller through elicitation which list to move `card_id` to,
+/// for `move_list` updates that did not say.
+async fn choose_list(backend: &dyn TaskBackend, client: &Notifier, card_id: &str) -> Result<String> {
+    if !client.client_supports("elicitation") {
+        return Err(anyhow::anyhow!("list_id is required for move_list"));
+    }
+    
+    let lists: Vec<TaskList> = backend.list_lists().await?
+        .into_iter()
+        .filter(|l| !l.closed)
+        .collect();
+    let answer = client.elicit(
+        &format!("Which list should task {} move to?", card_id),
+        json!({
+            "type": "object",
+            "properties": {
+                "list_id": {
+                    "type": "string",
+                    "title": "List",
+                    "enum": lists.iter().map(|l| &l.id).collect::<Vec<_>>(),
+                    "enumNames": lists.iter().map(|l| &l.name).collect::<Vec<_>>()
+                }
+            },
+            "required": ["list_id"]
+        }),
+    ).await?;
+    
+    answer
+        .and_then(|a| a["list_id"].as_str().map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("No list chosen; the task was not moved"))
+}
+
+pub struct ReleaseTask;
+
+#[async_trait]
+impl Tool for ReleaseTask {
+    const NAME: &'static str = "release_trello_task";
+    const DESCRIPTION: &'static str =
+        "Give a claimed task back so another agent can take it";
+
+    type Args = ReleaseTaskArgs;
+    type Output = TaskActionResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: ReleaseTaskArgs) -> Result<TaskActionResult> {
+        release_trello_task(ctx.redis, ctx.backend, args).await
+    }
+}
+
+pub async fn release_trello_task(
+    redis: &RedisManager,
+    backend: &dyn TaskBackend,
+    params: ReleaseTaskArgs,
+) -> Result<TaskActionResult> {
+    let mut conn = redis.get_connection().await?;
+    
+    // Touch the board before giving up the lease, so an agent whose move or
+    // comment failed still holds the card and can retry
+    assignment::verify_lease(&mut conn, &params.card_id, &params.agent_id, &params.lease_token).await?;
+    
+    // Hand the card back to the todo list so another agent can pick it up
+    backend.move_card(&params.card_id, &backend.workflow_lists().todo).await?;
+    
+    let reason = params.reason.unwrap_or_else(|| "no reason given".to_string());
+    backend.add_comment(
+        &params.card_id,
+        &format!("Task released by agent {}: {}", params.agent_id, reason),
+    ).await?;
+    
+    assignment::release(&mut conn, &params.card_id, &params.agent_id, &params.lease_token).await?;
+    
+    record_update(&mut conn, &params.card_id, json!({
+        "agent_id": params.agent_id,
+        "type": "release",
+        "outcome": "released",
+        "content": reason,
+        "timestamp": chrono::Utc::now().timestamp()
+    })).await?;
+    
+    Ok(TaskActionResult {
+        message: format!("Task {} released by agent {}", params.card_id, params.agent_id),
+        card_id: params.card_id,
+        agent_id: params.agent_id,
+    })
+}
+
+pub struct CompleteTask;
+
+#[async_trait]
+impl Tool for CompleteTask {
+    const NAME: &'static str = "complete_trello_task";
+    const DESCRIPTION: &'static str =
+        "Finish a claimed task, recording whether it succeeded and a summary of the work";
+
+    type Args = CompleteTaskArgs;
+    type Output = CompleteTaskResult;
+
+    async fn call(&self, ctx: &ToolContext<'_>, args: CompleteTaskArgs) -> Result<CompleteTaskResult> {
+        complete_trello_task(ctx.redis, ctx.backend, args).await
+    }
+}
+
+pub async fn complete_trello_task(
+    redis: &RedisManager,
+    backend: &dyn TaskBackend,
+    params: CompleteTaskArgs,
+) -> Result<CompleteTaskResult> {
+    let failure_reason = params.failure_reason.as_deref().filter(|reason| !reason.trim().is_empty());
+    if !params.success && failure_reason.is_none() {
+        return Err(InvalidArguments {
+            tool: CompleteTask::NAME.to_string(),
+            reason: "failure_reason is required when success is false".to_string(),
+        }
+        .into());
+    }
+
+    let mut conn = redis.get_connection().await?;
+    
+    // As with release, the lease goes last so a failed move can be retried
+    assignment::verify_lease(&mut conn, &params.card_id, &params.agent_id, &params.lease_token).await?;
+    
+    // Successful work goes to done; failed attempts go back to todo for a retry
+    let lists = backend.workflow_lists();
+    let (target_list, comment) = if params.success {
+        (
+            lists.done,
+            format!("Task completed by agent {}: {}", params.agent_id, params.summary),
+        )
+    } else {
+        (
+            lists.todo,
+            format!(
+                "Task failed under agent {}: {}\n\nSummary: {}",
+                params.agent_id,
+                failure_reason.unwrap_or_default(),
+                params.summary
+            ),
+        )
+    };
+    
+    backend.move_card(&params.card_id, &target_list).await?;
+    backend.add_comment(&params.card_id, &comment).await?;
+    
+    assignment::release(&mut conn, &params.card_id, &params.agent_id, &params.lease_token).await?;
     
-    let _: () = conn.set_ex(&update_key, update_data.to_string(), 86400 * 7).await?;
+    record_update(&mut conn, &params.card_id, json!({
+        "agent_id": params.agent_id,
+        "type": "complete",
+        "outcome": if params.success { "success" } else { "failure" },
+        "failure_reason": params.failure_reason,
+        "content": params.summary,
+        "timestamp": chrono::Utc::now().timestamp()
+    })).await?;
     
-    Ok(format!("Task {} updated successfully", params.card_id))
+    Ok(CompleteTaskResult {
+        message: format!(
+            "Task {} {} by agent {}",
+            params.card_id,
+            if params.success { "completed" } else { "marked as failed" },
+            params.agent_id
+        ),
+        card_id: params.card_id,
+        agent_id: params.agent_id,
+        success: params.success,
+        list_id: target_list,
+    })
+}
+
+/// List of every update recorded for `card_id`, oldest first.
+pub fn updates_key(card_id: &str) -> String {
+    format!("updates:{}", card_id)
+}
+
+/// Appends `update_data` to the card's update history, which is kept for a
+/// week after its latest entry.
+pub async fn record_update(
+    conn: &mut redis::aio::Connection,
+    card_id: &str,
+    update_data: Value,
+) -> Result<()> {
+    let update_key = updates_key(card_id);
+    let _: () = redis::pipe()
+        .atomic()
+        .rpush(&update_key, update_data.to_string())
+        .ignore()
+        .expire(&update_key, 86400 * 7)
+        .ignore()
+        .query_async(conn)
+        .await?;
+    Ok(())
 }
\ No newline at end of file
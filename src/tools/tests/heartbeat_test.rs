@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemas::HeartbeatArgs;
+    use crate::tests::support::test_redis;
+    use crate::tools::{assignment, heartbeat};
+    use crate::utils::get_lease_timeout;
+    use redis::AsyncCommands;
+    use uuid::Uuid;
+
+    fn beat(agent_id: &str, card_id: &str) -> HeartbeatArgs {
+        HeartbeatArgs {
+            agent_id: agent_id.to_string(),
+            card_id: card_id.to_string(),
+            status: "working".to_string(),
+            progress_percentage: Some(50.0),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_heartbeat_renews_only_the_owners_lease() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let card_id = Uuid::new_v4().to_string();
+        let key = assignment::assignment_key(&card_id);
+
+        assignment::claim(&mut conn, &card_id, "agent-a").await.unwrap().unwrap();
+        let _: () = conn.expire(&key, 5).await.unwrap();
+
+        // Another agent's heartbeat leaves the lease running down
+        let other = heartbeat::send_heartbeat(&redis, beat("agent-b", &card_id)).await.unwrap();
+        assert!(!other.lease_renewed);
+        let ttl: i64 = conn.ttl(&key).await.unwrap();
+        assert!(ttl <= 5);
+
+        // The owner's pushes it out to a full lease window
+        let owner = heartbeat::send_heartbeat(&redis, beat("agent-a", &card_id)).await.unwrap();
+        assert!(owner.lease_renewed);
+        let ttl: i64 = conn.ttl(&key).await.unwrap();
+        assert!(ttl > 5 && ttl <= get_lease_timeout() as i64);
+        assert_eq!(assignment::get(&mut conn, &card_id).await.unwrap().unwrap().agent_id, "agent-a");
+
+        let _: () = conn.del(&key).await.unwrap();
+        let _: () = conn.hdel(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+    }
+}
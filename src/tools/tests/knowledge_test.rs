@@ -0,0 +1,281 @@
+#[cfg(test)]
+mod tests {
+    use crate::notifications::Notifier;
+    use crate::tools::memory;
+    use crate::tests::support::test_redis;
+    use crate::utils::RedisManager;
+    use redis::JsonAsyncCommands;
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    fn args<T: DeserializeOwned>(value: Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Stores an error from agent-a and its solution from agent-b under a
+    /// topic no other test uses, and returns the topic and the entry ids.
+    async fn error_and_solution(redis: &RedisManager) -> (String, Vec<String>) {
+        let topic = format!("topic{}", Uuid::new_v4().simple());
+        let mut ids = Vec::new();
+        for (agent_id, category, content) in [
+            ("agent-a", "errors", format!("{} fails with a timeout", topic)),
+            ("agent-b", "solutions", format!("{} works with a longer timeout", topic)),
+        ] {
+            let stored = memory::store_knowledge(redis, args(json!({
+                "agent_id": agent_id, "category": category, "key": Uuid::new_v4().to_string(),
+                "content": content, "tags": []
+            }))).await.unwrap();
+            ids.push(stored.id);
+        }
+        (topic, ids)
+    }
+
+    /// Deletes entries a test stored, as their authors.
+    async fn clean_up(redis: &RedisManager, ids: &[String]) {
+        for id in ids {
+            if let Some(entry) = memory::get_knowledge(redis, id).await.unwrap() {
+                memory::delete_knowledge(redis, args(json!({"agent_id": entry.agent_id, "id": id})))
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_upsert_update_and_delete_knowledge() {
+        let redis = test_redis().await;
+        let key = Uuid::new_v4().to_string();
+
+        let store = |content: &str| args(json!({
+            "agent_id": "agent-a", "category": "api_docs", "key": key,
+            "content": content, "tags": ["docs"], "upsert": true
+        }));
+        let first = memory::store_knowledge(&redis, store("v1")).await.unwrap();
+        let second = memory::store_knowledge(&redis, store("v2")).await.unwrap();
+        assert!(!first.updated);
+        assert!(second.updated);
+        assert_eq!(first.id, second.id);
+        let id = first.id;
+
+        memory::update_knowledge(&redis, args(json!({"agent_id": "agent-b", "id": id, "content": "v3"})))
+            .await
+            .unwrap();
+        assert_eq!(memory::get_knowledge(&redis, &id).await.unwrap().unwrap().content, "v3");
+
+        // Only the author may delete
+        let delete = |agent_id: &str| args(json!({"agent_id": agent_id, "id": id}));
+        assert!(memory::delete_knowledge(&redis, delete("agent-b")).await.is_err());
+        memory::delete_knowledge(&redis, delete("agent-a")).await.unwrap();
+        assert!(memory::get_knowledge(&redis, &id).await.unwrap().is_none());
+        assert!(memory::delete_knowledge(&redis, delete("agent-a")).await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_knowledge_admin_can_delete_any_entry() {
+        let redis = test_redis().await;
+        std::env::set_var("KNOWLEDGE_ADMINS", "admin-x, knowledge-admin");
+
+        let stored = memory::store_knowledge(&redis, args(json!({
+            "agent_id": "agent-a", "category": "code_patterns", "key": Uuid::new_v4().to_string(),
+            "content": "retry with backoff", "tags": []
+        }))).await.unwrap();
+
+        let deleted = memory::delete_knowledge(&redis, args(json!({"agent_id": "knowledge-admin", "id": stored.id})))
+            .await
+            .unwrap();
+        assert_eq!(deleted.message, format!("Knowledge entry {} deleted by knowledge-admin", stored.id));
+        assert!(memory::get_knowledge(&redis, &stored.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_concurrent_upserts_share_one_entry() {
+        let redis = test_redis().await;
+        let key = Uuid::new_v4().to_string();
+
+        let upserts = (0..4).map(|n| {
+            let redis = redis.clone();
+            let params = args(json!({
+                "agent_id": "agent-a", "category": "api_docs", "key": key,
+                "content": format!("v{}", n), "tags": [], "upsert": true
+            }));
+            tokio::spawn(async move { memory::store_knowledge(&redis, params).await.unwrap() })
+        });
+        let mut stored = Vec::new();
+        for upsert in upserts {
+            stored.push(upsert.await.unwrap());
+        }
+
+        // One upsert created the entry, every other one replaced it
+        assert!(stored.iter().all(|s| s.id == stored[0].id));
+        assert_eq!(stored.iter().filter(|s| !s.updated).count(), 1);
+        let history = memory::get_knowledge_history(&redis, args(json!({"id": stored[0].id}))).await.unwrap();
+        assert_eq!(history.current_version, 4);
+        let versions: Vec<u32> = history.versions.iter().map(|v| v.version).collect();
+        assert_eq!(versions, (1..=4).collect::<Vec<_>>());
+        clean_up(&redis, &[stored[0].id.clone()]).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_all_knowledge_returns_every_entry() {
+        let redis = test_redis().await;
+
+        let mut ids = Vec::new();
+        for content in ["first", "second"] {
+            let stored = memory::store_knowledge(&redis, args(json!({
+                "agent_id": "agent-a", "category": "project_knowledge", "key": Uuid::new_v4().to_string(),
+                "content": content, "tags": []
+            }))).await.unwrap();
+            ids.push(stored.id);
+        }
+
+        let all = memory::all_knowledge(&redis).await.unwrap();
+        for id in &ids {
+            assert!(all.iter().any(|entry| &entry.id == id));
+        }
+        clean_up(&redis, &ids).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_knowledge_history_and_revert() {
+        let redis = test_redis().await;
+
+        let stored = memory::store_knowledge(&redis, args(json!({
+            "agent_id": "agent-a", "category": "api_docs", "key": Uuid::new_v4().to_string(),
+            "content": "GET /cards", "tags": ["cards"]
+        }))).await.unwrap();
+        let id = stored.id;
+
+        memory::update_knowledge(&redis, args(json!({
+            "agent_id": "agent-b", "id": id, "content": "GET /cards\nPOST /cards", "tags": ["cards", "write"]
+        }))).await.unwrap();
+
+        let history = memory::get_knowledge_history(&redis, args(json!({"id": id}))).await.unwrap();
+        assert_eq!(history.current_version, 2);
+        let second = &history.versions[1];
+        assert_eq!(second.agent_id, "agent-b");
+        assert_eq!(second.diff.content_added, vec!["POST /cards"]);
+        assert_eq!(second.diff.tags_added, vec!["write"]);
+
+        memory::revert_knowledge(&redis, args(json!({"agent_id": "agent-a", "id": id, "version": 1})))
+            .await
+            .unwrap();
+
+        let history = memory::get_knowledge_history(&redis, args(json!({"id": id}))).await.unwrap();
+        assert_eq!(history.current_version, 3);
+        assert_eq!(history.versions[2].reverted_from, Some(1));
+        assert_eq!(history.versions[2].content, "GET /cards");
+
+        // Concurrent updates each get a version of their own
+        let updates = (0..4).map(|n| {
+            let redis = redis.clone();
+            let params = args(json!({"agent_id": format!("agent-{}", n), "id": id, "content": format!("v{}", n)}));
+            tokio::spawn(async move { memory::update_knowledge(&redis, params).await.unwrap() })
+        });
+        for update in updates {
+            update.await.unwrap();
+        }
+        let history = memory::get_knowledge_history(&redis, args(json!({"id": id}))).await.unwrap();
+        assert_eq!(history.current_version, 7);
+        let versions: Vec<u32> = history.versions.iter().map(|v| v.version).collect();
+        assert_eq!(versions, (1..=7).collect::<Vec<_>>());
+        clean_up(&redis, &[id]).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_learn_from_agents_distills_through_sampling() {
+        let redis = test_redis().await;
+        let (topic, ids) = error_and_solution(&redis).await;
+        let (outbound, mut requests) = mpsc::channel(8);
+        let client = Arc::new(Notifier::new(outbound));
+        client.set_client_capabilities(json!({"sampling": {}}));
+
+        // Stands in for the client's model
+        let model = tokio::spawn({
+            let client = client.clone();
+            async move {
+                let request = requests.recv().await.unwrap();
+                assert_eq!(request["method"], "sampling/createMessage");
+                let text = "```json\n{\"common_patterns\": [\"timeouts\"], \
+                            \"error_solutions\": [{\"error\": \"timeout\", \"solution\": \"wait longer\"}]}\n```";
+                client.resolve(&json!({
+                    "jsonrpc": "2.0", "id": request["id"],
+                    "result": {"role": "assistant", "model": "stub-model", "content": {"type": "text", "text": text}}
+                }));
+            }
+        });
+
+        let learnings = memory::learn_from_agents(&redis, &client, args(json!({"topic": topic}))).await.unwrap();
+        model.await.unwrap();
+        assert_eq!(learnings.distilled_by.as_deref(), Some("stub-model"));
+        assert_eq!(learnings.common_patterns, vec![json!("timeouts")]);
+        assert_eq!(learnings.error_solutions, vec![json!({"error": "timeout", "solution": "wait longer"})]);
+        assert_eq!(learnings.agent_learnings.len(), 2);
+        clean_up(&redis, &ids).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_learn_from_agents_falls_back_to_raw_entries() {
+        let redis = test_redis().await;
+        let (topic, ids) = error_and_solution(&redis).await;
+        let (outbound, mut requests) = mpsc::channel(8);
+        let client = Arc::new(Notifier::new(outbound));
+
+        // A client without sampling is never asked
+        let learnings = memory::learn_from_agents(&redis, &client, args(json!({"topic": topic}))).await.unwrap();
+        assert!(requests.try_recv().is_err());
+        assert!(learnings.distilled_by.is_none());
+        assert_eq!(learnings.error_solutions.len(), 2);
+        assert_eq!(learnings.agent_learnings.len(), 2);
+
+        // One that fails the request gets the raw entries too
+        client.set_client_capabilities(json!({"sampling": {}}));
+        let model = tokio::spawn({
+            let client = client.clone();
+            async move {
+                let request = requests.recv().await.unwrap();
+                client.resolve(&json!({
+                    "jsonrpc": "2.0", "id": request["id"],
+                    "error": {"code": -1, "message": "User rejected sampling request"}
+                }));
+            }
+        });
+        let learnings = memory::learn_from_agents(&redis, &client, args(json!({"topic": topic}))).await.unwrap();
+        model.await.unwrap();
+        assert!(learnings.distilled_by.is_none());
+        assert_eq!(learnings.error_solutions.len(), 2);
+        clean_up(&redis, &ids).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_learn_from_agents_keeps_to_the_time_range() {
+        let redis = test_redis().await;
+        let (topic, ids) = error_and_solution(&redis).await;
+        let (outbound, _requests) = mpsc::channel(8);
+        let client = Arc::new(Notifier::new(outbound));
+
+        // The error was last touched two days ago
+        let two_days_ago = chrono::Utc::now().timestamp() - 2 * 86400;
+        let mut conn = redis.get_connection().await.unwrap();
+        for field in ["$.created_at", "$.updated_at"] {
+            let _: () = conn.json_set(format!("knowledge:{}", ids[0]), field, &two_days_ago).await.unwrap();
+        }
+
+        let learn = |time_range: &str| args(json!({"topic": topic, "time_range": time_range}));
+        let day = memory::learn_from_agents(&redis, &client, learn("day")).await.unwrap();
+        assert_eq!(day.agent_learnings.keys().collect::<Vec<_>>(), vec!["agent-b"]);
+        let week = memory::learn_from_agents(&redis, &client, learn("week")).await.unwrap();
+        assert_eq!(week.agent_learnings.len(), 2);
+        clean_up(&redis, &ids).await;
+    }
+}
@@ -0,0 +1,4 @@
+mod heartbeat_test;
+mod knowledge_test;
+mod registry_test;
+mod tasks_test;
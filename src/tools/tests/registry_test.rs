@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use crate::schemas::*;
+    use crate::tools::registry::ToolRegistry;
+    use jsonschema::JSONSchema;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+
+    fn tool(tools: &[Value], name: &str) -> Value {
+        tools.iter().find(|t| t["name"] == name).unwrap().clone()
+    }
+
+    #[test]
+    fn test_input_schemas_follow_argument_structs() {
+        let tools = ToolRegistry::builtin().list();
+        assert_eq!(tools.len(), 15);
+
+        let update = tool(&tools, "update_trello_task")["inputSchema"].clone();
+        assert_eq!(update["type"], "object");
+        assert_eq!(
+            update["properties"]["update_type"]["enum"],
+            json!(["comment", "checklist", "description", "move_list"])
+        );
+        assert!(update["properties"]["list_id"]["description"]
+            .as_str()
+            .unwrap()
+            .starts_with("For move_list only"));
+        let required = update["required"].as_array().unwrap();
+        assert!(required.contains(&json!("lease_token")));
+        assert!(!required.contains(&json!("list_id")));
+
+        let store = tool(&tools, "store_knowledge")["inputSchema"].clone();
+        assert_eq!(store["properties"]["category"]["enum"][0], "task_progress");
+
+        let complete = tool(&tools, "complete_trello_task")["inputSchema"].clone();
+        assert_eq!(complete["properties"]["success"]["default"], true);
+
+        let status = tool(&tools, "check_agent_status")["inputSchema"].clone();
+        assert_eq!(status["type"], "object");
+
+        assert_eq!(tool(&tools, "scan_trello_tasks")["annotations"]["readOnlyHint"], true);
+        assert_eq!(tool(&tools, "take_trello_task")["annotations"]["readOnlyHint"], false);
+        // Searching bumps access counts
+        assert_eq!(tool(&tools, "search_knowledge")["annotations"]["readOnlyHint"], false);
+    }
+
+    #[test]
+    fn test_long_searches_can_be_aborted() {
+        let tools = ToolRegistry::builtin();
+        assert!(tools.is_abortable("scan_trello_tasks"));
+        assert!(tools.is_abortable("search_knowledge"));
+        assert!(tools.is_abortable("learn_from_agents"));
+        assert!(!tools.is_abortable("take_trello_task"));
+        assert!(!tools.is_abortable("no_such_tool"));
+    }
+
+    #[test]
+    fn test_output_schemas_follow_result_structs() {
+        let tools = ToolRegistry::builtin().list();
+        for tool in &tools {
+            assert_eq!(tool["outputSchema"]["type"], "object", "{}", tool["name"]);
+        }
+
+        let take = tool(&tools, "take_trello_task")["outputSchema"].clone();
+        assert!(take["required"].as_array().unwrap().contains(&json!("lease_token")));
+
+        let scan = tool(&tools, "scan_trello_tasks")["outputSchema"].clone();
+        assert_eq!(
+            scan["properties"]["cards"]["items"]["properties"]["status"]["enum"],
+            json!(["available", "assigned"])
+        );
+    }
+
+    /// Fails unless `output`, serialized as a tool call returns it, is valid
+    /// against the `outputSchema` tool `name` advertises.
+    fn assert_matches_output_schema(tools: &[Value], name: &str, output: impl Serialize) {
+        let schema = JSONSchema::compile(&tool(tools, name)["outputSchema"]).unwrap();
+        let output = serde_json::to_value(output).unwrap();
+        let errors: Vec<String> = match schema.validate(&output) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.map(|e| format!("{} at {}", e, e.instance_path)).collect(),
+        };
+        assert!(errors.is_empty(), "{} result does not match its outputSchema: {:?}", name, errors);
+    }
+
+    #[test]
+    fn test_results_match_their_output_schemas() {
+        let tools = ToolRegistry::builtin().list();
+
+        // Unset optional fields are where results and schemas used to part ways
+        let card = ScannedCard {
+            id: "c1".to_string(),
+            name: "Write the docs".to_string(),
+            description: String::new(),
+            list_id: "todo".to_string(),
+            list_name: "To Do".to_string(),
+            status: CardStatus::Available,
+            assigned_to: None,
+            url: String::new(),
+            short_url: String::new(),
+            due: None,
+            due_complete: false,
+            labels: vec![TaskLabel { id: "l1".to_string(), name: "docs".to_string(), color: None }],
+            members: Vec::new(),
+        };
+        assert_matches_output_schema(&tools, "scan_trello_tasks", ScanTasksResult {
+            cards: vec![card],
+            total: 1,
+            lists: Vec::new(),
+            filters_applied: ListFilter::Todo,
+            next_cursor: None,
+        });
+
+        let version = KnowledgeVersion {
+            version: 1,
+            agent_id: "agent-a".to_string(),
+            timestamp: 0,
+            content: "GET /cards".to_string(),
+            tags: Vec::new(),
+            metadata: json!({}),
+            diff: KnowledgeDiff::default(),
+            reverted_from: None,
+        };
+        assert_matches_output_schema(&tools, "get_knowledge_history", KnowledgeHistoryResult {
+            id: "k1".to_string(),
+            current_version: 1,
+            versions: vec![version],
+        });
+
+        assert_matches_output_schema(&tools, "search_knowledge", SearchKnowledgeResult {
+            query: "cards".to_string(),
+            results: vec![json!({"id": "k1"})],
+            count: 1,
+            next_cursor: None,
+        });
+
+        assert_matches_output_schema(&tools, "learn_from_agents", LearningsResult {
+            topic: "cards".to_string(),
+            agent_learnings: [("agent-a".to_string(), vec![json!({"id": "k1"})])].into_iter().collect(),
+            common_patterns: Vec::new(),
+            error_solutions: Vec::new(),
+            distilled_by: None,
+        });
+    }
+}
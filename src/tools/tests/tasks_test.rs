@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests {
+    use crate::backends::memory::MemoryBackend;
+    use crate::backends::TaskBackend;
+    use crate::notifications::Notifier;
+    use crate::schemas::{TakeTaskArgs, TaskAssignment};
+    use crate::tests::support::{test_redis, unconnected_redis};
+    use crate::tools::registry::InvalidArguments;
+    use crate::tools::{assignment, heartbeat, tasks};
+    use crate::utils::RedisManager;
+    use redis::aio::Connection;
+    use redis::AsyncCommands;
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tokio::sync::mpsc::{self, Receiver};
+    use uuid::Uuid;
+
+    fn args<T: DeserializeOwned>(value: Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// A board holding one card that agent-a claimed an hour ago and has not
+    /// sent a heartbeat for since, and a client that supports elicitation.
+    async fn stale_claim(conn: &mut Connection) -> (MemoryBackend, String, Arc<Notifier>, Receiver<Value>) {
+        let backend = MemoryBackend::new();
+        let card_id = Uuid::new_v4().to_string();
+        backend.add_card(&card_id, "Write the docs", "in_progress");
+
+        let claim = TaskAssignment {
+            agent_id: "agent-a".to_string(),
+            lease_token: Uuid::new_v4().to_string(),
+            claimed_at: chrono::Utc::now().timestamp() - 3600,
+        };
+        let _: () = conn
+            .set_ex(assignment::assignment_key(&card_id), serde_json::to_string(&claim).unwrap(), 600)
+            .await
+            .unwrap();
+        let _: () = conn.hset(assignment::ASSIGNMENTS_INDEX, &card_id, "agent-a").await.unwrap();
+
+        let (outbound, requests) = mpsc::channel(8);
+        let client = Arc::new(Notifier::new(outbound));
+        client.set_client_capabilities(json!({"elicitation": {}}));
+        (backend, card_id, client, requests)
+    }
+
+    /// Answers the next elicitation request with `result`, after letting
+    /// agent-a send a heartbeat for `card_id` if `owner_returns`.
+    fn answer_takeover(
+        redis: &RedisManager,
+        client: &Arc<Notifier>,
+        mut requests: Receiver<Value>,
+        card_id: &str,
+        owner_returns: bool,
+        result: Value,
+    ) -> tokio::task::JoinHandle<()> {
+        let (redis, client, card_id) = (redis.clone(), client.clone(), card_id.to_string());
+        tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(request["method"], "elicitation/create");
+            if owner_returns {
+                heartbeat::send_heartbeat(&redis, args(json!({
+                    "agent_id": "agent-a", "card_id": card_id, "status": "back"
+                }))).await.unwrap();
+            }
+            client.resolve(&json!({"jsonrpc": "2.0", "id": request["id"], "result": result}));
+        })
+    }
+
+    async fn forget_claim(conn: &mut Connection, card_id: &str) {
+        let _: () = conn.del(assignment::assignment_key(card_id)).await.unwrap();
+        let _: () = conn.hdel(assignment::ASSIGNMENTS_INDEX, card_id).await.unwrap();
+        let _: () = conn.del(tasks::updates_key(card_id)).await.unwrap();
+        let _: () = conn.del(format!("heartbeat:agent-a:{}", card_id)).await.unwrap();
+    }
+
+    fn take(card_id: &str) -> TakeTaskArgs {
+        args(json!({"agent_id": "agent-b", "card_id": card_id}))
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_release_hands_the_card_back() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let (outbound, _rx) = mpsc::channel(8);
+        let client = Notifier::new(outbound);
+        let backend = MemoryBackend::new();
+        let card_id = Uuid::new_v4().to_string();
+        backend.add_card(&card_id, "Write the docs", "in_progress");
+        let lease = assignment::claim(&mut conn, &card_id, "agent-a").await.unwrap().unwrap();
+
+        let release = |lease_token: &str| args(json!({
+            "agent_id": "agent-a", "card_id": card_id, "lease_token": lease_token, "reason": "blocked"
+        }));
+        assert!(tasks::release_trello_task(&redis, &backend, release("not-the-token")).await.is_err());
+        assert_eq!(backend.get_card(&card_id).await.unwrap().list_id, "in_progress");
+        assert!(assignment::get(&mut conn, &card_id).await.unwrap().is_some());
+
+        // An update in the same second as the release keeps its own entry
+        tasks::update_trello_task(&redis, &backend, &client, args(json!({
+            "agent_id": "agent-a", "card_id": card_id, "update_type": "comment",
+            "content": "half done", "lease_token": lease.lease_token
+        }))).await.unwrap();
+        tasks::release_trello_task(&redis, &backend, release(&lease.lease_token)).await.unwrap();
+
+        assert_eq!(backend.get_card(&card_id).await.unwrap().list_id, "todo");
+        assert!(assignment::get(&mut conn, &card_id).await.unwrap().is_none());
+        let indexed: Option<String> = conn.hget(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+        assert!(indexed.is_none());
+        let held: bool = conn.sismember("agent:agent-a:tasks", &card_id).await.unwrap();
+        assert!(!held);
+
+        let history: Vec<String> = conn.lrange(tasks::updates_key(&card_id), 0, -1).await.unwrap();
+        let types: Vec<Value> = history.iter().map(|u| serde_json::from_str::<Value>(u).unwrap()["type"].clone()).collect();
+        assert_eq!(types, vec![json!("comment"), json!("release")]);
+        let _: () = conn.del(tasks::updates_key(&card_id)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_completion_needs_a_reason() {
+        let backend = MemoryBackend::new();
+        let complete = |reason: Value| args(json!({
+            "agent_id": "agent-a", "card_id": "card-1", "lease_token": "token",
+            "summary": "gave up", "success": false, "failure_reason": reason
+        }));
+
+        // Checked before Redis is ever reached
+        for reason in [Value::Null, json!(" ")] {
+            let error = tasks::complete_trello_task(&unconnected_redis(), &backend, complete(reason)).await.unwrap_err();
+            assert!(error.is::<InvalidArguments>(), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_accepted_takeover_reclaims_a_stale_task() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let (backend, card_id, client, requests) = stale_claim(&mut conn).await;
+
+        let accept = json!({"action": "accept", "content": {"take_over": true}});
+        let answer = answer_takeover(&redis, &client, requests, &card_id, false, accept);
+        let taken = tasks::take_trello_task(&redis, &backend, &client, take(&card_id)).await.unwrap();
+        answer.await.unwrap();
+
+        let owner = assignment::get(&mut conn, &card_id).await.unwrap().unwrap();
+        assert_eq!(owner.agent_id, "agent-b");
+        assert_eq!(owner.lease_token, taken.lease_token);
+        assert!(backend.comments.lock().unwrap()[&card_id][0].starts_with("Task taken over from unresponsive agent agent-a"));
+        let held: bool = conn.sismember("agent:agent-b:tasks", &card_id).await.unwrap();
+        assert!(held);
+        forget_claim(&mut conn, &card_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_declined_takeover_leaves_the_claim() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let (backend, card_id, client, requests) = stale_claim(&mut conn).await;
+
+        let answer = answer_takeover(&redis, &client, requests, &card_id, false, json!({"action": "decline"}));
+        let refused = tasks::take_trello_task(&redis, &backend, &client, take(&card_id)).await;
+        answer.await.unwrap();
+
+        assert!(refused.unwrap_err().to_string().contains("already assigned to agent-a"));
+        assert_eq!(assignment::get(&mut conn, &card_id).await.unwrap().unwrap().agent_id, "agent-a");
+        forget_claim(&mut conn, &card_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_live_owner_is_not_offered_for_takeover() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let (backend, card_id, client, mut requests) = stale_claim(&mut conn).await;
+        heartbeat::send_heartbeat(&redis, args(json!({
+            "agent_id": "agent-a", "card_id": card_id, "status": "working"
+        }))).await.unwrap();
+
+        let refused = tasks::take_trello_task(&redis, &backend, &client, take(&card_id)).await;
+        assert!(refused.is_err());
+        assert!(requests.try_recv().is_err());
+        assert_eq!(assignment::get(&mut conn, &card_id).await.unwrap().unwrap().agent_id, "agent-a");
+        forget_claim(&mut conn, &card_id).await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs Redis Stack at TEST_REDIS_URL"]
+    async fn test_takeover_loses_to_an_owner_that_came_back() {
+        let redis = test_redis().await;
+        let mut conn = redis.get_connection().await.unwrap();
+        let (backend, card_id, client, requests) = stale_claim(&mut conn).await;
+
+        // agent-a heartbeats, renewing its lease, while the caller is asked
+        let accept = json!({"action": "accept", "content": {"take_over": true}});
+        let answer = answer_takeover(&redis, &client, requests, &card_id, true, accept);
+        let refused = tasks::take_trello_task(&redis, &backend, &client, take(&card_id)).await;
+        answer.await.unwrap();
+
+        assert!(refused.is_err());
+        assert_eq!(assignment::get(&mut conn, &card_id).await.unwrap().unwrap().agent_id, "agent-a");
+        let indexed: Option<String> = conn.hget(assignment::ASSIGNMENTS_INDEX, &card_id).await.unwrap();
+        assert_eq!(indexed.as_deref(), Some("agent-a"));
+        forget_claim(&mut conn, &card_id).await;
+    }
+}
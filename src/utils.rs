@@ -1,7 +1,6 @@
 use anyhow::{Result, anyhow};
-use redis::{Client, aio::Connection, AsyncCommands, RedisResult};
+use redis::{Client, aio::Connection, RedisResult};
 use std::{env, fs};
-use serde_json::Value;
 
 const MAX_RETRIES: u32 = 3;
 const RETRY_DELAY: u64 = 1000; // milliseconds
@@ -13,6 +12,7 @@ pub struct RedisManager {
 }
 
 impl RedisManager {
+    #[allow(dead_code)]
     async fn ensure_modules_loaded(&self) -> Result<()> {
         let mut conn = self.get_connection().await?;
         
@@ -60,7 +60,7 @@ impl RedisManager {
         while retries < MAX_RETRIES {
             // Load and execute initialization script
             let script = fs::read_to_string("src/scripts/init_redis.lua")?;
-            let result: RedisResult<Value> = redis::cmd("EVAL")
+            let result: RedisResult<redis::Value> = redis::cmd("EVAL")
                 .arg(&script)
                 .arg(0) // No script keys
                 .query_async(&mut conn)
@@ -84,6 +84,21 @@ impl RedisManager {
         Err(anyhow!("Failed to initialize search after {} retries", MAX_RETRIES))
     }
 
+    /// A manager that only connects on first use, for tests that never get
+    /// that far.
+    #[cfg(test)]
+    pub fn unconnected(redis_url: &str) -> Result<Self> {
+        Ok(Self {
+            client: Client::open(redis_url)?,
+            initialized: false,
+        })
+    }
+
+    /// Whether the search index script has run.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub async fn get_connection(&self) -> Result<Connection> {
         Ok(self.client.get_async_connection().await?)
     }
@@ -100,6 +115,46 @@ pub fn get_trello_list_ids() -> (String, String, String) {
     (todo, in_progress, done)
 }
 
+/// Which `TaskBackend` to run against: `trello` (default), `local` or `github`.
+pub fn get_task_backend() -> String {
+    env::var("TASK_BACKEND").unwrap_or_else(|_| "trello".to_string())
+}
+
+/// Directory of task files used by the `local` backend.
+pub fn get_tasks_dir() -> String {
+    env::var("TASKS_DIR").unwrap_or_else(|_| "tasks".to_string())
+}
+
+pub fn get_local_list_ids() -> (String, String, String) {
+    let todo = env::var("TASKS_TODO_LIST").unwrap_or_else(|_| "todo".to_string());
+    let in_progress = env::var("TASKS_IN_PROGRESS_LIST").unwrap_or_else(|_| "in_progress".to_string());
+    let done = env::var("TASKS_DONE_LIST").unwrap_or_else(|_| "done".to_string());
+    
+    (todo, in_progress, done)
+}
+
+/// Settings for the `github` backend: API base URL, `owner/repo` and token.
+/// The base URL can point at GitHub, a self-hosted Gitea (`.../api/v1`) or a
+/// local mock.
+pub fn get_github_config() -> (String, String, String) {
+    let api_url = env::var("GITHUB_API_URL")
+        .unwrap_or_else(|_| "https://api.github.com".to_string());
+    let repo = env::var("GITHUB_REPO").expect("GITHUB_REPO must be set");
+    let token = env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN must be set");
+    
+    (api_url.trim_end_matches('/').to_string(), repo, token)
+}
+
+/// Labels that stand in for the todo / in progress / done lists on an issue
+/// tracker. Closed issues always count as done.
+pub fn get_github_list_labels() -> (String, String, String) {
+    let todo = env::var("GITHUB_TODO_LABEL").unwrap_or_else(|_| "todo".to_string());
+    let in_progress = env::var("GITHUB_IN_PROGRESS_LABEL").unwrap_or_else(|_| "in progress".to_string());
+    let done = env::var("GITHUB_DONE_LABEL").unwrap_or_else(|_| "done".to_string());
+    
+    (todo, in_progress, done)
+}
+
 pub fn get_heartbeat_timeout() -> u64 {
     env::var("HEARTBEAT_TIMEOUT")
         .unwrap_or_else(|_| "120".to_string())
@@ -107,6 +162,123 @@ pub fn get_heartbeat_timeout() -> u64 {
         .unwrap_or(120)
 }
 
+/// How long a task assignment survives without a heartbeat from its owner.
+/// Defaults to two heartbeat windows so a single late heartbeat is tolerated.
+pub fn get_lease_timeout() -> u64 {
+    env::var("LEASE_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| get_heartbeat_timeout() * 2)
+}
+
+/// Seconds between sweeps of the stale-assignment reaper.
+pub fn get_reaper_interval() -> u64 {
+    env::var("REAPER_INTERVAL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(get_heartbeat_timeout)
+}
+
+/// `stdio` (default) for a single agent on stdin/stdout, or `http` to serve
+/// many agents over Streamable HTTP.
+pub fn get_transport() -> String {
+    env::var("MCP_TRANSPORT").unwrap_or_else(|_| "stdio".to_string())
+}
+
+/// Address the HTTP transport binds to.
+pub fn get_http_address() -> (String, u16) {
+    let host = env::var("MCP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port = env::var("MCP_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(8080);
+    
+    (host, port)
+}
+
+/// Bearer token HTTP clients must send in `Authorization`. Required when
+/// `MCP_HOST` is not a loopback address.
+pub fn get_auth_token() -> Option<String> {
+    env::var("MCP_AUTH_TOKEN").ok().filter(|t| !t.is_empty())
+}
+
+/// How many JSON-RPC requests a stdio session processes at once, or all
+/// HTTP sessions together.
+pub fn get_max_concurrent_requests() -> usize {
+    env::var("MAX_CONCURRENT_REQUESTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(16)
+}
+
+/// How many more requests a stdio session holds while all its slots are
+/// busy; past that, new requests are turned away with an error.
+pub fn get_max_queued_requests() -> usize {
+    env::var("MAX_QUEUED_REQUESTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(64)
+}
+
+/// Seconds to wait for the client to answer a sampling or elicitation
+/// request before giving up on it.
+pub fn get_client_request_timeout() -> u64 {
+    env::var("CLIENT_REQUEST_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(120)
+}
+
+/// Messages queued per session for the client before new notifications
+/// are dropped
+pub fn get_outbound_buffer() -> usize {
+    env::var("OUTBOUND_BUFFER")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1024)
+}
+
+/// Seconds an HTTP session may go without requests (and without an open
+/// event stream) before it is closed
+pub fn get_session_idle_timeout() -> u64 {
+    env::var("MCP_SESSION_IDLE_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1800)
+}
+
+/// Origins besides loopback ones that may reach the HTTP transport from a
+/// browser
+pub fn get_allowed_origins() -> Vec<String> {
+    env::var("MCP_ALLOWED_ORIGINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty())
+        .collect()
+}
+
+/// Items per page for list methods and paginated tool results
+pub fn get_page_size() -> usize {
+    env::var("PAGE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(50)
+}
+
+/// Agents allowed to delete knowledge entries stored by someone else
+pub fn get_knowledge_admins() -> Vec<String> {
+    env::var("KNOWLEDGE_ADMINS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
 pub fn get_trello_config() -> (String, String, String) {
     let key = env::var("TRELLO_KEY").expect("TRELLO_KEY must be set");
     let token = env::var("TRELLO_TOKEN").unwrap_or_else(|_| {